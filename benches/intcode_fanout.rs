@@ -0,0 +1,58 @@
+use std::time::Instant;
+
+use aoc2019::shared::{Program, ProgramImage};
+
+const MACHINES: usize = 100_000;
+
+/// A Day 2 style program: a short run of arithmetic at the start of a large image.
+fn build_image() -> Vec<i64> {
+    let mut data = vec![1, 0, 0, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+    data.extend((0..4_000).map(|x| x % 97));
+    data
+}
+
+fn bench<F: FnMut(i64) -> i64>(name: &str, mut f: F) {
+    let start = Instant::now();
+    let mut checksum = 0i64;
+    for i in 0..MACHINES {
+        checksum = checksum.wrapping_add(f(i as i64));
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<24} {:>10.2?} total, {:>8.0?} per VM (checksum {})",
+        name,
+        elapsed,
+        elapsed / MACHINES as u32,
+        checksum
+    );
+}
+
+fn main() {
+    let data = build_image();
+    let image = ProgramImage::new(&data);
+
+    bench("copied image", |i| {
+        let mut program = Program::new(&data, &[]);
+        program[1] = i % 12;
+        program[2] = (i / 12) % 12;
+        program.run();
+        program[0]
+    });
+
+    bench("shared image", |i| {
+        let mut program = Program::from_image(&image, &[]);
+        program[1] = i % 12;
+        program[2] = (i / 12) % 12;
+        program.run();
+        program[0]
+    });
+
+    let mut parent = Program::from_image(&image, &[]);
+    parent[1] = 9;
+    bench("forked machine", |i| {
+        let mut program = parent.clone();
+        program[2] = i % 12;
+        program.run();
+        program[0]
+    });
+}
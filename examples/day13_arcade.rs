@@ -0,0 +1,122 @@
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use aoc2019::days::day13::{
+    compare_players, Arcade, ArcadeScreen, Autopilot, Joystick, Keyboard, Player, Predictive,
+    Replay,
+};
+use aoc2019::shared::ProgramImage;
+
+/// Waits for Enter before each recorded move, so a replay can be watched frame by frame.
+struct Stepper<R> {
+    replay: Replay,
+    input: R,
+}
+
+impl<R: BufRead> Player for Stepper<R> {
+    fn next_move(&mut self, arcade: &Arcade) -> Option<Joystick> {
+        print!("[Enter] next frame, [q] stop: ");
+        io::stdout().flush().ok()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line).ok()? == 0 || line.trim() == "q" {
+            return None;
+        }
+        self.replay.next_move(arcade)
+    }
+}
+
+fn draw(screen: &ArcadeScreen) {
+    // Clear the screen and draw from the top-left corner.
+    print!("\x1b[2J\x1b[H{}", screen.render(true));
+}
+
+/// Plays the Day 13 arcade game in the terminal.
+///
+/// Usage: `day13_arcade <input> [--autopilot | --predictive | --compare] [--record <dir>]`.
+/// Type `a`/`d` (or `h`/`l`) to move the paddle and `s` to hold still, one frame per key, then
+/// Enter. `q` gives up. With `--record`, every frame is saved to `dir` as a PPM image.
+/// `--compare` plays both automatic players without drawing and prints how many joystick moves
+/// each needed.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input = "input/2019/day13.txt".to_string();
+    let mut autopilot = false;
+    let mut predictive = false;
+    let mut compare = false;
+    let mut record = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--autopilot" => autopilot = true,
+            "--predictive" => predictive = true,
+            "--compare" => compare = true,
+            "--record" => record = args.next(),
+            _ => input = arg,
+        }
+    }
+    let image: ProgramImage = std::fs::read_to_string(input)?.parse()?;
+
+    if compare {
+        let (naive, predictive) = compare_players(&image, true);
+        println!(
+            "Joystick moves: {} predictive, {} following the ball",
+            predictive.moves, naive.moves
+        );
+        return Ok(());
+    }
+
+    let mut arcade = if record.is_some() {
+        Arcade::recording(&image, true)
+    } else {
+        Arcade::new(&image, true)
+    };
+    let score = if predictive {
+        arcade.play(&mut Predictive::default(), |screen| {
+            draw(screen);
+            std::thread::sleep(Duration::from_millis(20));
+        })
+    } else if autopilot {
+        arcade.play(&mut Autopilot, |screen| {
+            draw(screen);
+            std::thread::sleep(Duration::from_millis(20));
+        })
+    } else {
+        let stdin = io::stdin();
+        arcade.play(&mut Keyboard::new(stdin.lock()), draw)
+    };
+    let blocks = arcade.screen().blocks();
+    println!(
+        "{} with a score of {} after {} frames",
+        if blocks == 0 { "You win" } else { "Game over" },
+        score,
+        arcade.moves().len()
+    );
+    let stats = arcade.stats();
+    println!(
+        "{} joystick moves, {} blocks destroyed ({} moves per block), {} bounces",
+        stats.moves,
+        stats.blocks_destroyed,
+        stats
+            .moves_per_block()
+            .map_or("-".to_string(), |m| format!("{:.2}", m)),
+        stats.bounces
+    );
+    if let Some(dir) = record {
+        std::fs::create_dir_all(&dir)?;
+        arcade.screen().save_animation(&dir, 4)?;
+        println!("Saved {} frames to {}", arcade.screen().frames().len(), dir);
+    }
+
+    print!("Replay frame by frame? [y/N] ");
+    io::stdout().flush()?;
+    let stdin = io::stdin();
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+    if answer.trim().eq_ignore_ascii_case("y") {
+        let mut stepper = Stepper {
+            replay: Replay::new(arcade.moves().to_vec()),
+            input: stdin.lock(),
+        };
+        Arcade::new(&image, true).play(&mut stepper, draw);
+    }
+    Ok(())
+}
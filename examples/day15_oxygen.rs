@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use aoc2019::days::day15::render_oxygen_spread;
+use aoc2019::shared::ProgramImage;
+
+/// Watches oxygen fill the Day 15 ship, one frame per minute.
+///
+/// Usage: `day15_oxygen <input>`.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "input/2019/day15.txt".to_string());
+    let image: ProgramImage = std::fs::read_to_string(input)?.parse()?;
+
+    for frame in render_oxygen_spread(&image, true)? {
+        // Clear the screen and draw from the top-left corner.
+        print!("\x1b[2J\x1b[H{}", frame);
+        std::thread::sleep(Duration::from_millis(30));
+    }
+    Ok(())
+}
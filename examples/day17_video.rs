@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use aoc2019::days::day17::{watch_day17, VideoFeed};
+use aoc2019::shared::ProgramImage;
+
+/// Watches the Day 17 vacuum robot clean the scaffold.
+///
+/// Usage: `day17_video <input> [frame dir]`. Frames are drawn in the terminal, or saved as PPM
+/// images when a directory is given.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let input = args
+        .next()
+        .unwrap_or_else(|| "input/2019/day17.txt".to_string());
+    let image: ProgramImage = std::fs::read_to_string(input)?.parse()?;
+
+    let feed = match args.next() {
+        Some(dir) => {
+            std::fs::create_dir_all(&dir)?;
+            VideoFeed::Images {
+                dir: PathBuf::from(dir),
+                scale: 4,
+            }
+        }
+        None => VideoFeed::Terminal {
+            delay: Duration::from_millis(30),
+        },
+    };
+
+    let recording = watch_day17(&image, &feed)?;
+    println!("Frames: {}", recording.frames);
+    println!("Dust collected: {}", recording.dust);
+    Ok(())
+}
@@ -1,144 +1,55 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
+use crate::helper::{parse_map, AsteroidField, Grid, TileChar};
+
 // ======================================================
 // DAY 10
 // ======================================================
 
-#[aoc_generator(day10)]
-pub fn input_generator_day10(input: &str) -> Vec<(i64, i64)> {
-    input
-        .lines()
-        .enumerate()
-        .map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .filter(|(_, ch)| *ch == '#')
-                .map(move |(x, _ch)| (x as i64, y as i64))
-        })
-        .flatten()
-        .collect_vec()
-}
-
-fn dist_vec(a: &(i64, i64), b: &(i64, i64)) -> (i64, i64) {
-    ((b.0 - a.0), (b.1 - a.1))
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Space {
+    Empty,
+    Asteroid,
 }
 
-/// Returns true if `c` blocks LOS of `a` to `b`.
-fn is_blocking(a: &(i64, i64), b: &(i64, i64), c: &(i64, i64)) -> bool {
-    let dist_ba = dist_vec(b, a);
-    let dist_ca = dist_vec(c, a);
-    if dist_ba.0.signum() != dist_ca.0.signum() || dist_ba.1.signum() != dist_ca.1.signum() {
-        return false;
-    }
-    if dist_ca.0.abs() + dist_ca.1.abs() >= dist_ba.0.abs() + dist_ba.1.abs() {
-        // C is further from `a` than `b`; it cannot block LOS
-        return false;
+impl TileChar for Space {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            '.' => Some(Space::Empty),
+            '#' => Some(Space::Asteroid),
+            _ => None,
+        }
     }
 
-    dist_ba.0 * dist_ca.1 == dist_ca.0 * dist_ba.1
+    fn to_char(&self) -> char {
+        match self {
+            Space::Empty => '.',
+            Space::Asteroid => '#',
+        }
+    }
 }
 
-fn visible_asteroids(base: &(i64, i64), asteroids: &[(i64, i64)]) -> usize {
-    asteroids
-        .iter()
-        .filter(|&a| {
-            // Determine if there's another astroid blocking LOS
-            a != base && asteroids.iter().all(|b| !is_blocking(base, a, b))
-        })
-        .count()
+#[aoc_generator(day10)]
+pub fn input_generator_day10(input: &str) -> AsteroidField {
+    let map: Grid<Space> = parse_map(input).unwrap();
+    AsteroidField::new(map.find(&Space::Asteroid).collect_vec())
 }
 
 #[aoc(day10, part1)]
-pub fn solve_day10_part1(input: &[(i64, i64)]) -> usize {
-    let mut max_visible = 0;
-
-    for base in input.iter() {
-        let va = visible_asteroids(base, input);
-        max_visible = std::cmp::max(max_visible, va);
-    }
-
-    max_visible
+pub fn solve_day10_part1(input: &AsteroidField) -> usize {
+    input.best_station().map_or(0, |(_, visible)| visible)
 }
 
 #[aoc(day10, part2)]
-pub fn solve_day10_part2(input: &[(i64, i64)]) -> usize {
-    let base = {
-        let mut max_visible = 0;
-        let mut best_base = input[0];
-
-        for base in input.iter() {
-            let va = visible_asteroids(base, input);
-            if va > max_visible {
-                max_visible = va;
-                best_base = *base;
-            }
-        }
-        best_base
-    };
-
-    let mut asteroids = input.to_vec();
-    let mut counter = 199;
-    loop {
-        let va = visible_asteroids(&base, &asteroids) as i64;
-        if counter - va >= 0 {
-            let ast2 = asteroids.clone();
-            asteroids.retain(|a| a == &base || ast2.iter().any(|b| is_blocking(&base, a, b)));
-            counter -= va;
-        } else {
-            break;
-        }
-    }
-
-    // We now have `counter` asteroids to destroy, in order.
-    // However, we know all asteroids to be destroyed *must* be unblocked (otherwise, the loop would not have exited!)
-    {
-        let ast2 = asteroids.clone();
-        asteroids.retain(|a| a != &base && ast2.iter().all(|b| !is_blocking(&base, a, b)));
-    }
-
-    const PI_OVER_TWO: f64 = std::f64::consts::PI / 2f64;
-    const TWO_PI: f64 = std::f64::consts::PI * 2f64;
-
-    // Calculate the angles of each asteroid
-    let mut asteroid_angles = asteroids
-        .iter()
-        .map(|a| {
-            // We eventually want to sort by the angle between the asteroid and the base. To get the angle, we can use atan2()!
-            // In Rust, atan2 has self = y, and other = x -- that is, we call y.atan2(x)
-            // atan2 gives us the angle starting pointing right - we want to start pointing vertically! Thus, we add 90 degrees (or pi/2) to the resulting angle.
-            let mut angle = ((a.1 - base.1) as f64).atan2((a.0 - base.0) as f64) + PI_OVER_TWO;
-            // Re-constrain ourselves to the range [0, 2pi)
-            while angle >= TWO_PI {
-                angle -= TWO_PI;
-            }
-            while angle < 0f64 {
-                angle += TWO_PI;
-            }
-            (a, angle)
-        })
-        .collect_vec();
-
-    asteroid_angles.sort_unstable_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-    let selected_asteroid = asteroid_angles[counter as usize].0;
-
-    selected_asteroid.0 as usize * 100 + selected_asteroid.1 as usize
-}
-
-#[test]
-fn test_is_blocking() {
-    assert_eq!(is_blocking(&(3, 4), &(1, 0), &(2, 2)), true);
-    assert_eq!(is_blocking(&(3, 4), &(0, 0), &(2, 2)), false);
-
-    assert_eq!(is_blocking(&(0, 0), &(1, 0), &(2, 0)), false);
-    assert_eq!(is_blocking(&(0, 0), &(2, 0), &(1, 0)), true);
-    assert_eq!(is_blocking(&(0, 0), &(2, 0), &(2, 0)), false);
-
-    assert_eq!(is_blocking(&(0, 0), &(4, 6), &(2, 3)), true);
-    assert_eq!(is_blocking(&(0, 0), &(3, 9), &(2, 6)), true);
-
-    assert_eq!(is_blocking(&(1, 0), &(4, 3), &(3, 2)), true);
-    assert_eq!(is_blocking(&(1, 0), &(3, 4), &(2, 2)), true);
+pub fn solve_day10_part2(input: &AsteroidField) -> usize {
+    let (base, _) = input.best_station().expect("No asteroids");
+    let selected_asteroid = input
+        .vaporization_order(base)
+        .nth(199)
+        .expect("Fewer than 200 asteroids to vaporize");
+
+    selected_asteroid.x() as usize * 100 + selected_asteroid.y() as usize
 }
 
 #[test]
@@ -146,8 +57,8 @@ fn test_visible_asteroids() {
     let ex = ".#..#\n.....\n#####\n....#\n...##";
     let inp = input_generator_day10(ex);
 
-    let base = inp[0];
-    let va = visible_asteroids(&base, &inp);
+    let base = inp.asteroids()[0];
+    let va = inp.visible_from(base);
     assert_eq!(va, 7);
 }
 
@@ -255,3 +166,51 @@ fn test_pt2() {
     let res = solve_day10_part2(&inp);
     assert_eq!(res, 802);
 }
+
+#[test]
+fn test_vaporization_order() {
+    use crate::helper::Point;
+
+    let ex = ".#..##.###...#######
+##.############..##.
+.#.######.########.#
+.###.#######.####.#.
+#####.##.#.##.###.##
+..#####..#.#########
+####################
+#.####....###.#.#.##
+##.#################
+#####.##.###..####..
+..######..##.#######
+####.##.####...##..#
+.#####..#.######.###
+##...#.##########...
+#.##########.#######
+.####.#.###.###.#.##
+....##.##.###..#####
+.#.#.###########.###
+#.#.#.#####.####.###
+###.##.####.##.#..##";
+    let inp = input_generator_day10(ex);
+    let (base, _) = inp.best_station().unwrap();
+    assert_eq!(base, Point::new(11, 13));
+
+    let order = inp.vaporization_order(base).collect_vec();
+    assert_eq!(order.len(), inp.asteroids().len() - 1);
+    let expected = [
+        (1, (11, 12)),
+        (2, (12, 1)),
+        (3, (12, 2)),
+        (10, (12, 8)),
+        (20, (16, 0)),
+        (50, (16, 9)),
+        (100, (10, 16)),
+        (199, (9, 6)),
+        (200, (8, 2)),
+        (201, (10, 9)),
+        (299, (11, 1)),
+    ];
+    for &(n, p) in expected.iter() {
+        assert_eq!(order[n - 1], Point::from(p), "asteroid {}", n);
+    }
+}
@@ -1,21 +1,21 @@
+use crate::helper::{render_text, save_image, Grid, Palette, Point, Rgb};
 use crate::shared::*;
 use aoc_runner_derive::{aoc, aoc_generator};
+use std::io::{self, BufRead};
+use std::path::Path;
 
 // ======================================================
 // DAY 13
 // ======================================================
 
 #[aoc_generator(day13)]
-pub fn input_generator_day13(input: &str) -> Vec<i64> {
-    input
-        .split(',')
-        .map(|x| x.trim().parse().unwrap())
-        .collect()
+pub fn input_generator_day13(input: &str) -> Result<ProgramImage, ParseError> {
+    parse_program(input)
 }
 
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum Tile {
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Tile {
     Empty = 0,
     Wall = 1,
     Block = 2,
@@ -36,66 +36,607 @@ impl From<u8> for Tile {
     }
 }
 
-#[aoc(day13, part1)]
-pub fn solve_day13_part1(input: &[i64]) -> usize {
-    let mut program = Program::new(input, &[]);
-
-    let mut output_idx = 0;
-    let mut block_count = 0;
-
-    while program.get_status() != IntcodeStepResult::Halt {
-        program.run();
-        while output_idx < program.outputs.len() {
-            let _x = program.outputs[output_idx];
-            let _y = program.outputs[output_idx + 1];
-            let t: Tile = (program.outputs[output_idx + 2] as u8).into();
-            if t == Tile::Block {
-                block_count += 1;
+/// A joystick position, read by the game once per frame.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Joystick {
+    Left,
+    Neutral,
+    Right,
+}
+
+impl Joystick {
+    pub fn value(self) -> i64 {
+        match self {
+            Joystick::Left => -1,
+            Joystick::Neutral => 0,
+            Joystick::Right => 1,
+        }
+    }
+
+    /// The tilt that moves something at column `from` towards column `to`.
+    pub fn toward(from: i64, to: i64) -> Self {
+        match (to - from).signum() {
+            -1 => Joystick::Left,
+            1 => Joystick::Right,
+            _ => Joystick::Neutral,
+        }
+    }
+}
+
+/// The screen as it was when the game asked for the joystick.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub tiles: Grid<Tile>,
+    pub score: i64,
+}
+
+/// Everything the game has drawn so far, with a history of the ball, the score and, when
+/// recording, every frame.
+#[derive(Clone, Debug)]
+pub struct ArcadeScreen {
+    tiles: Grid<Tile>,
+    score: i64,
+    ball: Option<Point>,
+    paddle: Option<Point>,
+    /// Frames finished so far.
+    frame: usize,
+    start_blocks: Option<usize>,
+    trajectory: Vec<Point>,
+    scores: Vec<(usize, i64)>,
+    record: bool,
+    frames: Vec<Frame>,
+}
+
+impl Default for ArcadeScreen {
+    fn default() -> Self {
+        ArcadeScreen {
+            tiles: Grid::sparse(Tile::Empty),
+            score: 0,
+            ball: None,
+            paddle: None,
+            frame: 0,
+            start_blocks: None,
+            trajectory: vec![],
+            scores: vec![],
+            record: false,
+            frames: vec![],
+        }
+    }
+}
+
+fn palette() -> Palette<Tile> {
+    Palette::new(' ', Rgb::BLACK)
+        .with(Tile::Wall, '#', Rgb(128, 128, 128))
+        .with(Tile::Block, '=', Rgb(0, 160, 255))
+        .with(Tile::Paddle, '-', Rgb::WHITE)
+        .with(Tile::Ball, 'o', Rgb(255, 200, 0))
+}
+
+impl ArcadeScreen {
+    /// A screen that keeps a copy of every frame.
+    pub fn recording() -> Self {
+        ArcadeScreen {
+            record: true,
+            ..Default::default()
+        }
+    }
+
+    /// Applies one `(x, y, value)` triple from the game. `(-1, 0)` sets the score.
+    pub fn draw(&mut self, x: i64, y: i64, value: i64) {
+        if x == -1 && y == 0 {
+            if value != self.score {
+                self.scores.push((self.frame, value));
             }
+            self.score = value;
+            return;
+        }
+        let p = Point::new(x, y);
+        let tile: Tile = (value as u8).into();
+        match tile {
+            Tile::Ball => self.ball = Some(p),
+            Tile::Paddle => self.paddle = Some(p),
+            _ => (),
+        }
+        self.tiles.set(p, tile);
+    }
 
-            output_idx += 3;
+    /// Marks the end of a frame, once the game is waiting for the joystick or has stopped.
+    fn end_frame(&mut self) {
+        if self.start_blocks.is_none() {
+            self.start_blocks = Some(self.blocks());
+        }
+        if let Some(ball) = self.ball {
+            self.trajectory.push(ball);
+        }
+        if self.record {
+            self.frames.push(Frame {
+                tiles: self.tiles.to_dense(),
+                score: self.score,
+            });
         }
+        self.frame += 1;
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score
     }
 
-    block_count
+    pub fn ball(&self) -> Option<Point> {
+        self.ball
+    }
+
+    pub fn paddle(&self) -> Option<Point> {
+        self.paddle
+    }
+
+    pub fn tiles(&self) -> &Grid<Tile> {
+        &self.tiles
+    }
+
+    pub fn blocks(&self) -> usize {
+        self.tiles
+            .iter()
+            .filter(|&(_, &t)| t == Tile::Block)
+            .count()
+    }
+
+    /// Blocks broken since the first frame.
+    pub fn blocks_destroyed(&self) -> usize {
+        self.start_blocks.map_or(0, |start| start - self.blocks())
+    }
+
+    /// Where the ball was at the end of each frame.
+    pub fn trajectory(&self) -> &[Point] {
+        &self.trajectory
+    }
+
+    /// The frame each new score appeared in, and the score.
+    pub fn score_history(&self) -> &[(usize, i64)] {
+        &self.scores
+    }
+
+    /// How many times the ball changed direction, horizontally or vertically.
+    pub fn bounces(&self) -> usize {
+        let velocities: Vec<Point> = self
+            .trajectory
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .filter(|&v| v != Point::zero())
+            .collect();
+        velocities
+            .windows(2)
+            .filter(|w| (0..2).any(|i| w[0][i] * w[1][i] < 0))
+            .count()
+    }
+
+    /// Every frame so far, if the screen is recording.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// The screen and score as text, in colour when `ansi` is set.
+    pub fn render(&self, ansi: bool) -> String {
+        format!(
+            "{}Score: {}\n",
+            render_text(&self.tiles, &palette(), ansi),
+            self.score
+        )
+    }
+
+    /// Saves the recorded frames to `dir` as `frame_0000.ppm`, `frame_0001.ppm`... with every
+    /// tile `scale` pixels square.
+    pub fn save_animation<P: AsRef<Path>>(&self, dir: P, scale: usize) -> io::Result<()> {
+        let palette = palette();
+        for (idx, frame) in self.frames.iter().enumerate() {
+            let path = dir.as_ref().join(format!("frame_{:04}.ppm", idx));
+            save_image(&frame.tiles, &palette, scale, path)?;
+        }
+        Ok(())
+    }
 }
 
-#[aoc(day13, part2)]
-pub fn solve_day13_part2(input: &[i64]) -> i64 {
-    let mut program = Program::new(input, &[]);
-    program[0] = 2;
-
-    let mut output_idx = 0;
-    let mut score = 0;
-
-    let mut ball_pos_x = 0;
-    let mut paddle_pos_x = 0;
-
-    while program.get_status() != IntcodeStepResult::Halt {
-        program.run();
-        while output_idx < program.outputs.len() {
-            let x = program.outputs[output_idx];
-            let y = program.outputs[output_idx + 1];
-            if x == -1 && y == 0 {
-                score = program.outputs[output_idx + 2];
-            } else {
-                let t: Tile = (program.outputs[output_idx + 2] as u8).into();
-
-                if t == Tile::Paddle {
-                    paddle_pos_x = x;
-                } else if t == Tile::Ball {
-                    ball_pos_x = x;
+/// Works the joystick, one frame at a time.
+pub trait Player {
+    /// The joystick position for the next frame, or `None` to stop playing. Players can look
+    /// at the screen, or fork the arcade to try moves out.
+    fn next_move(&mut self, arcade: &Arcade) -> Option<Joystick>;
+}
+
+/// Keeps the paddle under the ball.
+pub struct Autopilot;
+
+impl Player for Autopilot {
+    fn next_move(&mut self, arcade: &Arcade) -> Option<Joystick> {
+        let screen = arcade.screen();
+        Some(match (screen.paddle(), screen.ball()) {
+            (Some(paddle), Some(ball)) => Joystick::toward(paddle.x(), ball.x()),
+            _ => Joystick::Neutral,
+        })
+    }
+}
+
+/// Forks the game to find where the ball will next come down, moves the paddle there and then
+/// holds still, rather than chasing the ball back and forth.
+#[derive(Default)]
+pub struct Predictive {
+    target: Option<i64>,
+}
+
+impl Player for Predictive {
+    fn next_move(&mut self, arcade: &Arcade) -> Option<Joystick> {
+        let screen = arcade.screen();
+        let (paddle, ball) = match (screen.paddle(), screen.ball()) {
+            (Some(paddle), Some(ball)) => (paddle, ball),
+            _ => return Some(Joystick::Neutral),
+        };
+
+        if ball.y() == paddle.y() - 1 && self.target == Some(ball.x()) {
+            // The ball is about to bounce off the paddle, so stay put and aim again next frame.
+            self.target = None;
+            return Some(Joystick::toward(paddle.x(), ball.x()));
+        }
+        if self.target.is_none() {
+            self.target = arcade.landing();
+        }
+        Some(Joystick::toward(
+            paddle.x(),
+            self.target.unwrap_or(ball.x()),
+        ))
+    }
+}
+
+/// Reads moves from lines of keys: `a`/`h` for left, `d`/`l` for right and `s`/`.`/space for
+/// neutral. Each key is one frame, and an empty line is a single neutral frame. `q` stops
+/// playing once the keys before it have been played, as does the end of the input.
+pub struct Keyboard<R> {
+    input: R,
+    queued: std::collections::VecDeque<Joystick>,
+    quit: bool,
+}
+
+impl<R: BufRead> Keyboard<R> {
+    pub fn new(input: R) -> Self {
+        Keyboard {
+            input,
+            queued: Default::default(),
+            quit: false,
+        }
+    }
+}
+
+impl<R: BufRead> Player for Keyboard<R> {
+    fn next_move(&mut self, _arcade: &Arcade) -> Option<Joystick> {
+        while self.queued.is_empty() && !self.quit {
+            let mut line = String::new();
+            if self.input.read_line(&mut line).ok()? == 0 {
+                return None;
+            }
+            let line = line.trim_end_matches(&['\r', '\n'][..]);
+            if line.is_empty() {
+                self.queued.push_back(Joystick::Neutral);
+            }
+            for key in line.chars() {
+                match key.to_ascii_lowercase() {
+                    'a' | 'h' => self.queued.push_back(Joystick::Left),
+                    'd' | 'l' => self.queued.push_back(Joystick::Right),
+                    's' | '.' | ' ' => self.queued.push_back(Joystick::Neutral),
+                    'q' => {
+                        self.quit = true;
+                        break;
+                    }
+                    _ => (),
                 }
             }
+        }
+        self.queued.pop_front()
+    }
+}
+
+/// Plays back recorded moves, then stops.
+pub struct Replay {
+    moves: std::vec::IntoIter<Joystick>,
+}
+
+impl Replay {
+    pub fn new(moves: Vec<Joystick>) -> Self {
+        Replay {
+            moves: moves.into_iter(),
+        }
+    }
+}
+
+impl Player for Replay {
+    fn next_move(&mut self, _arcade: &Arcade) -> Option<Joystick> {
+        self.moves.next()
+    }
+}
+
+/// How a game went.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GameStats {
+    /// Frames played, one joystick reading each.
+    pub frames: usize,
+    /// Frames with the joystick tilted.
+    pub moves: usize,
+    pub blocks_destroyed: usize,
+    pub bounces: usize,
+}
+
+impl GameStats {
+    pub fn moves_per_block(&self) -> Option<f64> {
+        if self.blocks_destroyed == 0 {
+            None
+        } else {
+            Some(self.moves as f64 / self.blocks_destroyed as f64)
+        }
+    }
+}
+
+/// The arcade cabinet: runs the game, keeps the screen up to date and records every move.
+pub struct Arcade {
+    program: Program,
+    screen: ArcadeScreen,
+    output_idx: usize,
+    moves: Vec<Joystick>,
+}
+
+impl Arcade {
+    /// Boots the game and draws the first frame. With `free_play` no quarters are needed and
+    /// the game can actually be played.
+    pub fn new(image: &ProgramImage, free_play: bool) -> Self {
+        Arcade::with_screen(image, free_play, ArcadeScreen::default())
+    }
+
+    /// Like `new`, but keeps every frame for replays and animations.
+    pub fn recording(image: &ProgramImage, free_play: bool) -> Self {
+        Arcade::with_screen(image, free_play, ArcadeScreen::recording())
+    }
+
+    fn with_screen(image: &ProgramImage, free_play: bool, screen: ArcadeScreen) -> Self {
+        let mut program = Program::from_image(image, &[]);
+        if free_play {
+            program[0] = 2;
+        }
+        let mut arcade = Arcade {
+            program,
+            screen,
+            output_idx: 0,
+            moves: vec![],
+        };
+        arcade.refresh();
+        arcade
+    }
+
+    /// Runs the game until it wants the joystick or halts, drawing everything it outputs.
+    fn refresh(&mut self) {
+        self.program.run();
+        let outputs = &self.program.outputs;
+        while self.output_idx + 3 <= outputs.len() {
+            let idx = self.output_idx;
+            self.screen
+                .draw(outputs[idx], outputs[idx + 1], outputs[idx + 2]);
+            self.output_idx += 3;
+        }
+        self.screen.end_frame();
+    }
+
+    pub fn screen(&self) -> &ArcadeScreen {
+        &self.screen
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.program.get_status() == IntcodeStepResult::Halt
+    }
+
+    /// Every joystick position played so far, one per frame.
+    pub fn moves(&self) -> &[Joystick] {
+        &self.moves
+    }
+
+    pub fn stats(&self) -> GameStats {
+        GameStats {
+            frames: self.moves.len(),
+            moves: self
+                .moves
+                .iter()
+                .filter(|&&m| m != Joystick::Neutral)
+                .count(),
+            blocks_destroyed: self.screen.blocks_destroyed(),
+            bounces: self.screen.bounces(),
+        }
+    }
+
+    /// A copy of the game that can be played ahead without touching this one. The copy keeps
+    /// the current screen but none of the history.
+    pub fn fork(&self) -> Self {
+        Arcade {
+            program: self.program.clone(),
+            screen: ArcadeScreen {
+                tiles: self.screen.tiles.clone(),
+                score: self.screen.score,
+                ball: self.screen.ball,
+                paddle: self.screen.paddle,
+                ..Default::default()
+            },
+            output_idx: self.output_idx,
+            moves: vec![],
+        }
+    }
+
+    /// The column where the ball next reaches the row just above the paddle, found by playing
+    /// a fork of the game ahead. `None` if the game ends first.
+    pub fn landing(&self) -> Option<i64> {
+        let row = self.screen.paddle()?.y() - 1;
+        let mut fork = self.fork();
+        while !fork.is_over() {
+            fork.step(Joystick::Neutral);
+            match fork.screen.ball() {
+                Some(ball) if ball.y() == row => return Some(ball.x()),
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Plays one frame with the joystick held at `joystick`.
+    pub fn step(&mut self, joystick: Joystick) {
+        self.moves.push(joystick);
+        self.program.add_input(joystick.value());
+        self.refresh();
+    }
+
+    /// Lets `player` play until the game ends or it stops, calling `on_frame` with each frame
+    /// including the first. Returns the final score.
+    pub fn play<P: Player, F: FnMut(&ArcadeScreen)>(
+        &mut self,
+        player: &mut P,
+        mut on_frame: F,
+    ) -> i64 {
+        on_frame(&self.screen);
+        while !self.is_over() {
+            match player.next_move(self) {
+                Some(joystick) => self.step(joystick),
+                None => break,
+            }
+            on_frame(&self.screen);
+        }
+        self.screen.score()
+    }
+}
+
+#[aoc(day13, part1)]
+pub fn solve_day13_part1(input: &ProgramImage) -> usize {
+    Arcade::new(input, false).screen().blocks()
+}
+
+#[aoc(day13, part2)]
+pub fn solve_day13_part2(input: &ProgramImage) -> i64 {
+    Arcade::new(input, true).play(&mut Autopilot, |_| ())
+}
+
+#[aoc(day13, part2, predictive)]
+pub fn solve_day13_part2_predictive(input: &ProgramImage) -> i64 {
+    Arcade::new(input, true).play(&mut Predictive::default(), |_| ())
+}
+
+/// Plays the game once following the ball and once predicting where it lands, and returns how
+/// each went, in that order.
+pub fn compare_players(image: &ProgramImage, free_play: bool) -> (GameStats, GameStats) {
+    let mut naive = Arcade::new(image, free_play);
+    naive.play(&mut Autopilot, |_| ());
+    let mut predictive = Arcade::new(image, free_play);
+    predictive.play(&mut Predictive::default(), |_| ());
+    (naive.stats(), predictive.stats())
+}
 
-            output_idx += 3;
+/// A game that draws each frame's `(x, y, tile)` triples in turn, reading the joystick (and
+/// ignoring it) between frames.
+#[cfg(test)]
+fn scripted_game(frames: &[&[[i64; 3]]]) -> ProgramImage {
+    let mut code = vec![];
+    for (idx, frame) in frames.iter().enumerate() {
+        if idx > 0 {
+            code.extend(&[3, 1000]);
         }
+        for triple in frame.iter() {
+            code.extend(triple.iter().flat_map(|&v| vec![104, v]));
+        }
+    }
+    code.push(99);
+    ProgramImage::new(&code)
+}
+
+#[test]
+fn test_day13_screen() {
+    let mut screen = ArcadeScreen::default();
+    for &[x, y, v] in &[
+        [0, 0, 1],
+        [1, 0, 2],
+        [2, 0, 2],
+        [1, 2, 4],
+        [0, 3, 3],
+        [-1, 0, 12],
+    ] {
+        screen.draw(x, y, v);
+    }
+    assert_eq!(screen.blocks(), 2);
+    assert_eq!(screen.ball(), Some(Point::new(1, 2)));
+    assert_eq!(screen.paddle(), Some(Point::new(0, 3)));
+    assert_eq!(screen.render(false), "#==\n   \n o \n-  \nScore: 12\n");
+
+    screen.draw(1, 0, 0);
+    assert_eq!(screen.blocks(), 1);
+}
 
-        if program.get_status() == IntcodeStepResult::WaitingForInput {
-            let mv = (ball_pos_x - paddle_pos_x).signum();
-            program.add_input(mv);
+#[test]
+fn test_day13_keyboard() {
+    use Joystick::*;
+
+    let arcade = Arcade::new(&scripted_game(&[]), false);
+    let mut keyboard = Keyboard::new(&b"aad\n\nxl q\nd\n"[..]);
+    let moves: Vec<Joystick> = std::iter::from_fn(|| keyboard.next_move(&arcade)).collect();
+    assert_eq!(moves, vec![Left, Left, Right, Neutral, Right, Neutral]);
+}
+
+#[test]
+fn test_day13_history() {
+    let mut screen = ArcadeScreen::recording();
+    screen.draw(0, 0, 2);
+    screen.draw(1, 0, 2);
+    // The ball bounces off the right wall, the block above it and then the left wall.
+    let path = [(1, 3), (2, 2), (1, 1), (0, 2), (1, 3)];
+    for (frame, &(x, y)) in path.iter().enumerate() {
+        if frame > 0 {
+            let (px, py) = path[frame - 1];
+            screen.draw(px, py, 0);
         }
+        if (x, y) == (1, 1) {
+            screen.draw(1, 0, 0);
+            screen.draw(-1, 0, 10);
+        }
+        screen.draw(x, y, 4);
+        screen.end_frame();
     }
 
-    score
+    assert_eq!(screen.trajectory().len(), 5);
+    assert_eq!(screen.score_history(), &[(2, 10)]);
+    assert_eq!(screen.blocks_destroyed(), 1);
+    assert_eq!(screen.bounces(), 3);
+    assert_eq!(screen.frames().len(), 5);
+    assert_eq!(screen.frames()[1].score, 0);
+    assert_eq!(screen.frames()[4].score, 10);
+    assert_eq!(screen.frames()[4].tiles[Point::new(1, 3)], Tile::Ball);
+    assert_eq!(screen.frames()[4].tiles[Point::new(0, 2)], Tile::Empty);
+}
+
+#[test]
+fn test_day13_players() {
+    // The ball falls diagonally towards column 1. The script moves the paddle there from the
+    // left whatever the joystick does.
+    let game = scripted_game(&[
+        &[[0, 3, 3], [3, 0, 4]],
+        &[[3, 0, 0], [2, 1, 4], [0, 3, 0], [1, 3, 3]],
+        &[[2, 1, 0], [1, 2, 4]],
+    ]);
+    let arcade = Arcade::new(&game, false);
+    assert_eq!(arcade.screen().ball(), Some(Point::new(3, 0)));
+    assert_eq!(Autopilot.next_move(&arcade), Some(Joystick::Right));
+
+    // Looking ahead leaves the game itself alone.
+    assert_eq!(arcade.landing(), Some(1));
+    assert_eq!(arcade.screen().ball(), Some(Point::new(3, 0)));
+    assert!(arcade.moves().is_empty());
+
+    let mut arcade = Arcade::new(&game, false);
+    let mut predictive = Predictive::default();
+    arcade.play(&mut predictive, |_| ());
+    assert_eq!(arcade.moves(), &[Joystick::Right, Joystick::Neutral]);
+    assert_eq!(arcade.stats().moves, 1);
+
+    let mut naive = Arcade::new(&game, false);
+    naive.play(&mut Autopilot, |_| ());
+    assert_eq!(naive.moves(), &[Joystick::Right, Joystick::Right]);
+    // Predicting the landing needs fewer joystick inputs than chasing the ball.
+    let (naive, predictive) = compare_players(&game, false);
+    assert_eq!((naive.moves, predictive.moves), (2, 1));
 }
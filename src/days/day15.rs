@@ -1,7 +1,12 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::{HashMap, HashSet, VecDeque};
 
-use crate::helper::Point;
+use std::collections::HashSet;
+use std::fmt;
+use std::iter::once;
+
+use crate::helper::{
+    render_text, Cell, Droid, Explorer, Flood, Grid, Heading, Palette, Point, Rgb, Status,
+};
 use crate::shared::*;
 
 // ======================================================
@@ -9,186 +14,221 @@ use crate::shared::*;
 // ======================================================
 
 #[aoc_generator(day15)]
-pub fn input_generator_day15(input: &str) -> Vec<i64> {
-    input
-        .split(',')
-        .map(|x| x.trim().parse().unwrap())
-        .collect()
+pub fn input_generator_day15(input: &str) -> Result<ProgramImage, ParseError> {
+    parse_program(input)
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 enum Tile {
     Empty,
-    Wall,
     Oxygen,
 }
 
-#[aoc(day15, part1)]
-pub fn solve_day15_part1(input: &[i64]) -> usize {
-    let mut program = Program::new(input, &[]);
-
-    let mut output_idx = 0;
-    let mut nodes: HashMap<Point, Tile> = HashMap::default();
-    let mut curr_pos = Point::new(0, 0);
-    nodes.insert(curr_pos, Tile::Empty);
-    let mut positions: Vec<Point> = vec![];
-
-    'outer: while program.get_status() != IntcodeStepResult::Halt {
-        // Decide where to move
-        let deltas = vec![
-            Point::new(0, 1),
-            Point::new(0, -1),
-            Point::new(-1, 0),
-            Point::new(1, 0),
-        ];
-
-        // Look for unexplored deltas
-        let movement = deltas
-            .iter()
-            .find(|d| !nodes.contains_key(&(curr_pos + **d)))
-            .copied()
-            .unwrap_or_else(|| positions.pop().expect("No previous locations") - curr_pos);
-
-        // Move in that direction
-        program.add_input(match movement {
-            Point { x: 0, y: -1 } => 1, // North
-            Point { x: 0, y: 1 } => 2,  // South
-            Point { x: -1, y: 0 } => 3, // West
-            Point { x: 1, y: 0 } => 4,  // East
-            _ => panic!("Invalid movement {:?}", movement),
-        });
-        program.run();
-
-        // Check robot outputs
-        while output_idx < program.outputs.len() {
-            match program.outputs[output_idx] {
-                0 => {
-                    // Hit a wall. Location unchanged.
-                    nodes.insert(curr_pos + movement, Tile::Wall);
-                }
-                1 => {
-                    // Moved one step in the requested direction.
-                    if !nodes.contains_key(&(curr_pos + movement)) {
-                        // We are not backtracking
-                        positions.push(curr_pos);
-                    }
-                    curr_pos += movement;
-
-                    nodes.insert(curr_pos, Tile::Empty);
-                }
-                2 => {
-                    // Moved one step in the requested direction. New position is the oxygen
-                    // subsystem.
-                    // We are guaranteed to not be backtracking
-                    positions.push(curr_pos);
-                    curr_pos += movement;
-                    nodes.insert(curr_pos, Tile::Oxygen);
-                    break 'outer;
-                }
-                _ => panic!("Unexpected output {}", program.outputs[output_idx]),
-            }
-            output_idx += 1;
+/// The repair droid, driven over its Intcode move/status protocol.
+struct RepairDroid {
+    program: Program,
+}
+
+impl RepairDroid {
+    fn new(input: &ProgramImage) -> Self {
+        RepairDroid {
+            program: Program::from_image(input, &[]),
         }
     }
+}
 
-    positions.len()
+fn move_code(heading: Heading) -> i64 {
+    match heading {
+        Heading::North => 1,
+        Heading::South => 2,
+        Heading::West => 3,
+        Heading::East => 4,
+    }
 }
 
-#[aoc(day15, part2)]
-pub fn solve_day15_part2(input: &[i64]) -> usize {
-    let mut program = Program::new(input, &[]);
-
-    let mut output_idx = 0;
-    let mut nodes: HashMap<Point, Tile> = HashMap::default();
-    let mut curr_pos = Point::new(0, 0);
-    nodes.insert(curr_pos, Tile::Empty);
-    let mut positions: Vec<Point> = vec![];
-
-    let mut oxygen = Point::new(0, 0);
-
-    let deltas = vec![
-        Point::new(0, 1),
-        Point::new(0, -1),
-        Point::new(-1, 0),
-        Point::new(1, 0),
-    ];
-
-    'outer: while program.get_status() != IntcodeStepResult::Halt {
-        // Decide where to move
-        // Look for unexplored deltas
-        let movement = deltas
-            .iter()
-            .find(|d| !nodes.contains_key(&(curr_pos + **d)))
-            .copied()
-            .unwrap_or_else(|| positions.pop().unwrap_or(curr_pos + Point::new(-1, -1)) - curr_pos);
-        if movement == Point::new(-1, -1) {
-            break 'outer; // We've fully explored the map
+impl Droid for RepairDroid {
+    type Tile = Tile;
+
+    fn try_move(&mut self, heading: Heading) -> Status<Tile> {
+        if self.program.get_status() == IntcodeStepResult::Halt {
+            return Status::Stopped;
         }
+        let replies = self.program.outputs.len();
+        self.program.add_input(move_code(heading));
+        self.program.run();
+
+        match self.program.outputs.get(replies) {
+            Some(0) => Status::Blocked,
+            Some(1) => Status::Moved(Tile::Empty),
+            Some(2) => Status::Moved(Tile::Oxygen),
+            Some(x) => panic!("Unexpected output {}", x),
+            None => Status::Stopped,
+        }
+    }
+}
 
-        // Move in that direction
-        program.add_input(match movement {
-            Point { x: 0, y: -1 } => 1, // North
-            Point { x: 0, y: 1 } => 2,  // South
-            Point { x: -1, y: 0 } => 3, // West
-            Point { x: 1, y: 0 } => 4,  // East
-            _ => panic!("Invalid movement {:?}", movement),
-        });
-        program.run();
-
-        // Check robot outputs
-        while output_idx < program.outputs.len() {
-            match program.outputs[output_idx] {
-                0 => {
-                    // Hit a wall. Location unchanged.
-                    nodes.insert(curr_pos + movement, Tile::Wall);
-                }
-                1 => {
-                    // Moved one step in the requested direction.
-                    if !nodes.contains_key(&(curr_pos + movement)) {
-                        // We are not backtracking
-                        positions.push(curr_pos);
-                    }
-                    curr_pos += movement;
-
-                    nodes.insert(curr_pos, Tile::Empty);
-                }
-                2 => {
-                    // Moved one step in the requested direction. New position is the oxygen
-                    // subsystem.
-                    if !nodes.contains_key(&(curr_pos + movement)) {
-                        // We are not backtracking
-                        positions.push(curr_pos);
-                    }
-                    curr_pos += movement;
-                    nodes.insert(curr_pos, Tile::Oxygen);
-                    oxygen = curr_pos;
+/// Why the ship couldn't be mapped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShipError {
+    /// The droid stopped before reaching these cells next to explored ones.
+    Unreachable(Vec<Point>),
+    /// Every cell was explored without finding the oxygen system.
+    NoOxygenSystem,
+}
+
+impl fmt::Display for ShipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShipError::Unreachable(cells) => {
+                write!(f, "the droid couldn't reach {} cells:", cells.len())?;
+                for p in cells {
+                    write!(f, " ({}, {})", p.x(), p.y())?;
                 }
-                _ => panic!("Unexpected output {}", program.outputs[output_idx]),
+                Ok(())
             }
-            output_idx += 1;
+            ShipError::NoOxygenSystem => write!(f, "the ship has no oxygen system"),
         }
     }
+}
+
+impl std::error::Error for ShipError {}
+
+/// The fully explored ship, and where the oxygen system is.
+fn explore_ship(input: &ProgramImage) -> Result<(Explorer<RepairDroid>, Point), ShipError> {
+    let mut explorer = Explorer::new(RepairDroid::new(input), Tile::Empty);
+    let unexplored = explorer.explore();
+    if !unexplored.is_empty() {
+        return Err(ShipError::Unreachable(unexplored));
+    }
 
-    // Calculate how long the maximal path away from the oxygen location is
-    let mut checked: HashSet<Point> = HashSet::default();
-    let mut boundary: VecDeque<Point> = VecDeque::new();
-    let mut lengths: HashMap<Point, usize> = HashMap::default();
+    let oxygen = explorer
+        .map()
+        .find(&Cell::Open(Tile::Oxygen))
+        .next()
+        .ok_or(ShipError::NoOxygenSystem)?;
+    Ok((explorer, oxygen))
+}
 
-    lengths.insert(oxygen, 0);
-    boundary.push_back(oxygen);
+#[aoc(day15, part1)]
+pub fn solve_day15_part1(input: &ProgramImage) -> Result<usize, ShipError> {
+    let (explorer, oxygen) = explore_ship(input)?;
+    let path = explorer
+        .path(Point::new(0, 0), oxygen)
+        .expect("No path to the oxygen system");
+    Ok(path.len() - 1)
+}
 
-    while !boundary.is_empty() {
-        let to_check = boundary.pop_front().unwrap();
-        checked.insert(to_check);
+/// Oxygen spreading from every `sources` cell into the open cells of the map, a minute at
+/// a time.
+fn oxygen_spread<'a, I: IntoIterator<Item = Point>>(
+    map: &'a Grid<Cell<Tile>>,
+    sources: I,
+) -> Flood<impl FnMut(Point) -> bool + 'a> {
+    Flood::new(sources, move |p| map.get(p).is_some_and(|c| c.is_open()))
+}
 
-        for &d in deltas.iter() {
-            let pos = d + to_check;
-            if !checked.contains(&pos) && nodes[&pos] != Tile::Wall {
-                lengths.insert(pos, lengths[&to_check] + 1);
-                boundary.push_back(pos);
-            }
-        }
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+enum Shade {
+    Unknown,
+    Wall,
+    Open,
+    Filled,
+}
+
+/// Draws the map with the cells in `filled` full of oxygen.
+fn render_oxygen(map: &Grid<Cell<Tile>>, filled: &HashSet<Point>, ansi: bool) -> String {
+    let mut shades = map.map(|c| match c {
+        Cell::Unknown => Shade::Unknown,
+        Cell::Wall => Shade::Wall,
+        Cell::Open(_) => Shade::Open,
+    });
+    for &p in filled {
+        shades.set(p, Shade::Filled);
     }
+    let palette = Palette::new(' ', Rgb::BLACK)
+        .with(Shade::Wall, '#', Rgb(128, 128, 128))
+        .with(Shade::Open, '.', Rgb(60, 60, 60))
+        .with(Shade::Filled, 'O', Rgb(80, 160, 255));
+    render_text(&shades, &palette, ansi)
+}
 
-    *lengths.values().max().unwrap()
+/// Explores the ship and draws the oxygen filling it, one frame per minute.
+pub fn render_oxygen_spread(input: &ProgramImage, ansi: bool) -> Result<Vec<String>, ShipError> {
+    let (explorer, oxygen) = explore_ship(input)?;
+    let map = explorer.map();
+    Ok(oxygen_spread(map, once(oxygen))
+        .map(|step| {
+            format!(
+                "{}Minute {}\n",
+                render_oxygen(map, &step.filled, ansi),
+                step.minute
+            )
+        })
+        .collect())
+}
+
+#[aoc(day15, part2)]
+pub fn solve_day15_part2(input: &ProgramImage) -> Result<usize, ShipError> {
+    let (explorer, oxygen) = explore_ship(input)?;
+    Ok(oxygen_spread(explorer.map(), once(oxygen)).duration())
+}
+
+#[cfg(test)]
+fn parse_ship(map: &str) -> Grid<Cell<Tile>> {
+    let rows = map
+        .lines()
+        .map(|l| {
+            l.chars()
+                .map(|c| match c {
+                    '#' => Cell::Wall,
+                    '.' => Cell::Open(Tile::Empty),
+                    'O' => Cell::Open(Tile::Oxygen),
+                    _ => Cell::Unknown,
+                })
+                .collect()
+        })
+        .collect();
+    Grid::from_rows(rows).unwrap()
+}
+
+#[test]
+fn test_day15_oxygen_spread() {
+    let map = parse_ship(" ##   \n#..## \n#.#..#\n#.O.# \n ###  ");
+    let oxygen = map.find(&Cell::Open(Tile::Oxygen)).next().unwrap();
+    let steps: Vec<_> = oxygen_spread(&map, once(oxygen)).collect();
+    assert_eq!(steps.len(), 5);
+    assert_eq!(steps.last().unwrap().minute, 4);
+    assert_eq!(
+        render_oxygen(&map, &steps[1].filled, false),
+        " ##   \n#..## \n#.#..#\n#OOO# \n ###  \n"
+    );
+    assert_eq!(
+        render_oxygen(&map, &steps[4].filled, false),
+        " ##   \n#OO## \n#O#OO#\n#OOO# \n ###  \n"
+    );
+
+    // Releasing oxygen at both ends of the ship instead fills it faster.
+    let both = oxygen_spread(&map, vec![Point::new(1, 1), Point::new(3, 2)]);
+    assert_eq!(both.duration(), 2);
+}
+
+#[test]
+fn test_day15_unreachable() {
+    // A droid that halts straight away leaves the start's neighbours unexplored.
+    let image = parse_program("99").unwrap();
+    let err = solve_day15_part1(&image).unwrap_err();
+    assert_eq!(
+        err,
+        ShipError::Unreachable(vec![
+            Point::new(0, -1),
+            Point::new(-1, 0),
+            Point::new(1, 0),
+            Point::new(0, 1),
+        ])
+    );
+    assert_eq!(
+        err.to_string(),
+        "the droid couldn't reach 4 cells: (0, -1) (-1, 0) (1, 0) (0, 1)"
+    );
+    assert_eq!(solve_day15_part2(&image), Err(err));
 }
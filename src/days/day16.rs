@@ -1,5 +1,7 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::helper::{Fft, Window};
+
 // ======================================================
 // DAY 16
 // ======================================================
@@ -12,15 +14,6 @@ pub fn input_generator_day16(input: &str) -> Vec<u8> {
         .collect()
 }
 
-#[test]
-fn test_day16_process_1() {
-    let mut input: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
-    process(&mut input);
-    assert_eq!(input, [4, 8, 2, 2, 6, 1, 5, 8]);
-    process(&mut input);
-    assert_eq!(input, [3, 4, 0, 4, 0, 4, 3, 8]);
-}
-
 #[test]
 fn test_day16_process_2() {
     let input = input_generator_day16("80871224585914546619083218645595");
@@ -42,36 +35,40 @@ fn test_day16_process_4() {
     assert_eq!(res, 52432133);
 }
 
-fn process(input: &mut [u8]) {
-    const PATTERN: [i8; 4] = [0, 1, 0, -1];
-    let mut new_input = vec![0u8; input.len()];
-    for digit in 1..=input.len() {
-        let pattern = PATTERN
-            .iter()
-            .flat_map(|&v| std::iter::repeat(v).take(digit))
-            .cycle()
-            .skip(1);
-        let digit_sum: i64 = input
-            .iter()
-            .zip(pattern)
-            .map(|(a, b)| (*a as i64) * (b as i64))
-            .sum();
-        let final_digit = i64::abs(digit_sum % 10);
-        new_input[digit - 1] = final_digit as u8;
-    }
-
-    input.clone_from_slice(&new_input);
-}
-
 fn to_number(input: &[u8]) -> usize {
     input.iter().fold(0usize, |acc, x| acc * 10 + (*x as usize))
 }
 
 #[aoc(day16, part1)]
 pub fn solve_day16_part1(input: &[u8]) -> usize {
-    let mut v = input.to_vec();
-    for _i in 0..100 {
-        process(&mut v);
-    }
-    to_number(&v[..8])
+    to_number(&Fft::new().run(input))
+}
+
+#[aoc(day16, part2)]
+pub fn solve_day16_part2(input: &[u8]) -> usize {
+    let fft = Fft::new()
+        .with_repeat(10_000)
+        .with_window(Window::FromPrefix { digits: 7, len: 8 });
+    to_number(&fft.run(input))
+}
+
+#[test]
+fn test_day16_part2_1() {
+    let input = input_generator_day16("03036732577212944063491565474664");
+    let res = solve_day16_part2(&input);
+    assert_eq!(res, 84462026);
+}
+
+#[test]
+fn test_day16_part2_2() {
+    let input = input_generator_day16("02935109699940807407585447034323");
+    let res = solve_day16_part2(&input);
+    assert_eq!(res, 78725270);
+}
+
+#[test]
+fn test_day16_part2_3() {
+    let input = input_generator_day16("03081770884921959731165446850517");
+    let res = solve_day16_part2(&input);
+    assert_eq!(res, 53553731);
 }
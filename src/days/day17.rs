@@ -1,7 +1,15 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-
-use crate::helper::{replace_all_with, Point};
+use std::collections::HashSet;
+use std::error::Error;
+use std::iter::once;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::helper::{
+    parse_ascii_output, parse_map, render_text, save_image, Compressor, Grid, Heading, Move,
+    Palette, Point, Rgb, Routines, TileChar, Turn,
+};
 use crate::shared::*;
 
 // ======================================================
@@ -9,30 +17,21 @@ use crate::shared::*;
 // ======================================================
 
 #[aoc_generator(day17)]
-pub fn input_generator_day17(input: &str) -> Vec<i64> {
-    input
-        .split(',')
-        .map(|x| x.trim().parse().unwrap())
-        .collect()
-}
-
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
+pub fn input_generator_day17(input: &str) -> Result<ProgramImage, ParseError> {
+    parse_program(input)
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum Tile {
+pub enum Tile {
     Empty,
     Scaffold,
-    Robot(Dir),
+    Robot(Heading),
+    /// The robot after falling off the scaffold.
+    Tumbling,
 }
 
 impl Tile {
-    fn get_dir(self) -> Option<Dir> {
+    fn get_dir(self) -> Option<Heading> {
         if let Tile::Robot(d) = self {
             Some(d)
         } else {
@@ -41,196 +40,145 @@ impl Tile {
     }
 }
 
-fn get_map(input: &[i64]) -> Vec<Vec<Tile>> {
-    let mut program = Program::new(input, &[]);
-    program.run();
-
-    let mut map = vec![vec![]];
-    let mut y = 0;
-
-    // Build the map
-    for &ch in program.outputs.iter() {
-        if ch == 35 {
-            // #
-            map[y].push(Tile::Scaffold);
-        } else if ch == 46 {
-            // .
-            map[y].push(Tile::Empty);
-        } else if ch == 94 {
-            // ^
-            map[y].push(Tile::Robot(Dir::Up));
-        } else if ch == 60 {
-            // <
-            map[y].push(Tile::Robot(Dir::Left));
-        } else if ch == 62 {
-            // >
-            map[y].push(Tile::Robot(Dir::Right));
-        } else if ch == 118 {
-            // v
-            map[y].push(Tile::Robot(Dir::Down));
+impl TileChar for Tile {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            '#' => Some(Tile::Scaffold),
+            '.' => Some(Tile::Empty),
+            '^' | '<' | '>' | 'v' => Heading::from_char(ch).map(Tile::Robot),
+            'X' => Some(Tile::Tumbling),
+            _ => None,
         }
-        if ch == 10 {
-            y += 1;
-            map.push(vec![]);
-        }
-    }
-    if map.last().unwrap().is_empty() {
-        map.pop(); // Remove trailing newline
     }
-    map
-}
 
-#[aoc(day17, part1)]
-pub fn solve_day15_part1(input: &[i64]) -> i64 {
-    let map = get_map(input);
-    println!("Map: {} x {}", map.len(), map[0].len());
-
-    let mut intersections = vec![];
-
-    for y in 1..map.len() - 1 {
-        for x in 1..map[y].len() - 1 {
-            let cell = map[y][x];
-            if cell == Tile::Scaffold {
-                // Check cardinal directions
-                if map[y][x - 1] == Tile::Scaffold
-                    && map[y][x + 1] == Tile::Scaffold
-                    && map[y - 1][x] == Tile::Scaffold
-                    && map[y + 1][x] == Tile::Scaffold
-                {
-                    // We have an intersection
-                    intersections.push(Point::new(x as i64, y as i64));
-                }
-            }
+    fn to_char(&self) -> char {
+        match self {
+            Tile::Scaffold => '#',
+            Tile::Empty => '.',
+            Tile::Robot(h) => h.to_arrow(),
+            Tile::Tumbling => 'X',
         }
     }
-
-    intersections.iter().map(|p| p.x * p.y).sum()
 }
 
-/*
+/// The scaffold as the cameras see it before the robot is woken up.
+pub fn get_map(input: &ProgramImage) -> Grid<Tile> {
+    let mut program = Program::from_image(input, &[]);
+    program.run();
 
-A------------- B------- B------- A------------- C--------- B------- C--------- C--------- B------- A-------------
-R10 R8 L10 L10 R8 L6 L6 R8 L6 L6 R10 R8 L10 L10 L10 R10 L6 R8 L6 L6 L10 R10 L6 L10 R10 L6 R8 L6 L6 R10 R8 L10 L10
+    parse_ascii_output(&program.outputs).expect("Invalid camera output")
+}
 
-A - R10 R8 L10 L10
-B - R8 L6 L6
-C - L10 R10 L6
+#[aoc(day17, part1)]
+pub fn solve_day15_part1(input: &ProgramImage) -> i64 {
+    let map = get_map(input);
+    println!("Map: {} x {}", map.height(), map.width());
 
-*/
+    let intersections = map
+        .iter()
+        .filter(|&(p, &cell)| {
+            // Check cardinal directions
+            cell == Tile::Scaffold
+                && map
+                    .neighbours4(p)
+                    .filter(|&(_, &t)| t == Tile::Scaffold)
+                    .count()
+                    == 4
+        })
+        .map(|(p, _)| p)
+        .collect_vec();
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum Rotation {
-    Right,
-    Left,
+    intersections.iter().map(|p| p.x() * p.y()).sum()
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum Direction {
-    Rotation(Rotation),
-    Movement(usize),
-    Subroutine(usize),
+fn is_valid_position(pos: Point, map: &Grid<Tile>) -> bool {
+    map.get(pos).is_some_and(|&t| t != Tile::Empty)
 }
 
-impl std::fmt::Display for Direction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Direction::Rotation(Rotation::Left) => write!(f, "L"),
-            Direction::Rotation(Rotation::Right) => write!(f, "R"),
-            Direction::Movement(len) => write!(f, "{}", len),
-            Direction::Subroutine(0) => write!(f, "A"),
-            Direction::Subroutine(1) => write!(f, "B"),
-            Direction::Subroutine(2) => write!(f, "C"),
-            Direction::Subroutine(x) => panic!("Unexpected subroutine {}", x),
-        }
-    }
+/// Where the robot starts and which way it faces.
+fn find_robot(map: &Grid<Tile>) -> (Point, Heading) {
+    map.iter()
+        .find_map(|(p, t)| t.get_dir().map(|d| (p, d)))
+        .expect("No robot on the map")
 }
 
-fn get_delta_for_direction(dir: Dir) -> Point {
-    match dir {
-        Dir::Up => Point::new(0, -1),
-        Dir::Right => Point::new(1, 0),
-        Dir::Down => Point::new(0, 1),
-        Dir::Left => Point::new(-1, 0),
-    }
+/// The outcome of walking a movement routine offline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Simulation {
+    /// Whether the robot went over every scaffold cell.
+    pub visited_all: bool,
+    /// The first cell off the scaffold the robot was told to move onto. The robot stops there.
+    pub fell_off: Option<Point>,
+    /// Where the robot ended up, always on the scaffold.
+    pub position: Point,
+    pub heading: Heading,
 }
 
-fn rotate(dir: Dir, rot: Rotation) -> Dir {
-    match (rot, dir) {
-        (Rotation::Right, Dir::Up) => Dir::Right,
-        (Rotation::Right, Dir::Right) => Dir::Down,
-        (Rotation::Right, Dir::Down) => Dir::Left,
-        (Rotation::Right, Dir::Left) => Dir::Up,
-        (Rotation::Left, Dir::Up) => Dir::Left,
-        (Rotation::Left, Dir::Left) => Dir::Down,
-        (Rotation::Left, Dir::Down) => Dir::Right,
-        (Rotation::Left, Dir::Right) => Dir::Up,
+/// Walks `routines` over the scaffold from the robot's starting position, the way the robot
+/// would, without running the Intcode program.
+pub fn simulate(map: &Grid<Tile>, routines: &Routines) -> Simulation {
+    let (mut position, mut heading) = find_robot(map);
+    let mut visited: HashSet<Point> = once(position).collect();
+    let mut fell_off = None;
+
+    let moves = routines.main.iter().flat_map(|&f| &routines.functions[f]);
+    'walk: for m in moves {
+        match *m {
+            Move::Turn(t) => heading = heading.turn(t),
+            Move::Forward(n) => {
+                for _ in 0..n {
+                    let next = position + heading;
+                    if !is_valid_position(next, map) {
+                        fell_off = Some(next);
+                        break 'walk;
+                    }
+                    position = next;
+                    visited.insert(position);
+                }
+            }
+        }
     }
-}
 
-fn is_valid_position(pos: Point, map: &[Vec<Tile>]) -> bool {
-    if pos.y < 0 || pos.y >= map.len() as i64 {
-        return false;
-    }
-    if pos.x < 0 || pos.x >= map[pos.y as usize].len() as i64 {
-        return false;
+    let visited_all = map
+        .iter()
+        .filter(|&(_, &t)| t != Tile::Empty)
+        .all(|(p, _)| visited.contains(&p));
+    Simulation {
+        visited_all,
+        fell_off,
+        position,
+        heading,
     }
-    map[pos.y as usize][pos.x as usize] != Tile::Empty
 }
 
-#[aoc(day17, part2)]
-pub fn solve_day15_part2(input: &[i64]) -> i64 {
-    let map = get_map(input);
-
-    // Find the robot's current location
-    let tiles = map
-        .iter()
-        .enumerate()
-        .flat_map(|(y, r)| r.iter().enumerate().map(move |(x, t)| (x, y, t)))
-        .collect_vec();
-
-    let (mut robot_pos, mut robot_dir) = {
-        let rp = tiles
-            .iter()
-            .find(|(_, _, &t)| match t {
-                Tile::Robot(_) => true,
-                _ => false,
-            })
-            .unwrap();
-        (
-            Point::new(rp.0 as i64, rp.1 as i64),
-            rp.2.get_dir().unwrap(),
-        )
-    };
+/// Traces the scaffold from the robot's start and packs the path into the robot's routines.
+fn plan_routines(map: &Grid<Tile>) -> Result<Routines, Box<dyn Error>> {
+    let (mut robot_pos, mut robot_dir) = find_robot(map);
 
     let mut directions = vec![];
     let mut movement = 0;
     loop {
         // Check if we can keep going
-        let delta = get_delta_for_direction(robot_dir);
-        let new_pos = robot_pos + delta;
-        if is_valid_position(new_pos, &map) {
+        let new_pos = robot_pos + robot_dir;
+        if is_valid_position(new_pos, map) {
             movement += 1;
             robot_pos = new_pos;
             continue;
         }
 
         if movement > 0 {
-            directions.push(Direction::Movement(movement));
+            directions.push(Move::Forward(movement));
             movement = 0;
         }
 
         // Check to see if we can rotate to a valid direction
-        let delta_right = get_delta_for_direction(rotate(robot_dir, Rotation::Right));
-        if is_valid_position(robot_pos + delta_right, &map) {
-            directions.push(Direction::Rotation(Rotation::Right));
-            robot_dir = rotate(robot_dir, Rotation::Right);
-            continue;
-        }
-
-        let delta_left = get_delta_for_direction(rotate(robot_dir, Rotation::Left));
-        if is_valid_position(robot_pos + delta_left, &map) {
-            directions.push(Direction::Rotation(Rotation::Left));
-            robot_dir = rotate(robot_dir, Rotation::Left);
+        if let Some(turn) = [Turn::Right, Turn::Left]
+            .iter()
+            .copied()
+            .find(|&t| is_valid_position(robot_pos + robot_dir.turn(t), map))
+        {
+            directions.push(Move::Turn(turn));
+            robot_dir = robot_dir.turn(turn);
             continue;
         }
 
@@ -239,64 +187,254 @@ pub fn solve_day15_part2(input: &[i64]) -> i64 {
     }
 
     // Now that we have the movement code, we need to compress it into 3 subroutines.
-    let mut subroutines: [Vec<Direction>; 3] = [vec![], vec![], vec![]];
-    let mut master = directions.clone();
-
-    for (s_idx, s) in subroutines.iter_mut().enumerate() {
-        let mut idx = 0;
-        while idx < master.len() {
-            // Skip subroutine calls
-            if let Direction::Subroutine(_) = master[idx] {
-                if !s.is_empty() {
-                    // Replace all instances of the subroutine in `master` with this particular subroutine call
-                    replace_all_with(&mut master, &s, &[Direction::Subroutine(s_idx)]);
-
-                    // We're 1 + s.len() beyond the start of s. We remove s.len() - 1 elements from the array,
-                    // and we want to progress 1 further.
-                    // To stay at the same point, we subtract s.len() - 1 from idx.
-                    // Then we progress forward by 1, for a total subtraction of s.len() - 2.
-                    idx -= s.len() - 2;
-                } else {
-                    idx += 1;
-                }
-                continue;
-            }
+    let routines = Compressor::new().compress(&directions)?;
+    let sim = simulate(map, &routines);
+    if !sim.visited_all || sim.fell_off.is_some() {
+        return Err(format!("routines don't cover the scaffold: {:?}", sim).into());
+    }
+    Ok(routines)
+}
 
-            s.push(master[idx]);
-
-            // Check if there's more than 1 of `s` in master
-            if master
-                .as_slice()
-                .windows(s.len())
-                .filter(|&p| p == s.as_slice())
-                .count()
-                <= 1
-            {
-                // If not, we go back to the last valid configuration
-                s.remove(s.len() - 1);
-                // Replace all instances of the subroutine in `master` with this particular subroutine call
-                replace_all_with(&mut master, &s, &[Direction::Subroutine(s_idx)]);
-                break;
-            } else {
-                idx += 1;
-            }
+/// Wakes the robot up, feeds it the routines and returns everything it prints.
+fn run_robot(input: &ProgramImage, routines: &Routines, video_feed: bool) -> Vec<i64> {
+    let mut inputs = format!("{}\n", routines.main_routine());
+    // The robot always asks for all three functions, even if some go unused.
+    for f in 0..3 {
+        if f < routines.functions.len() {
+            inputs += &routines.function(f);
         }
+        inputs += "\n";
     }
-
-    let main_routine = format!("{}\n", master.iter().format(","));
-    let routine_a = format!("{}\n", subroutines[0].iter().format(","));
-    let routine_b = format!("{}\n", subroutines[1].iter().format(","));
-    let routine_c = format!("{}\n", subroutines[2].iter().format(","));
-    let show_video = "n\n";
-    let inputs = format!(
-        "{}{}{}{}{}",
-        main_routine, routine_a, routine_b, routine_c, show_video
-    );
+    inputs += if video_feed { "y\n" } else { "n\n" };
     let inputs_ascii = inputs.chars().map(|c| c as i64).collect_vec();
-    let mut program = Program::new(input, &inputs_ascii);
+    let mut program = Program::from_image(input, &inputs_ascii);
     program[0] = 2;
 
     program.run();
+    program.outputs
+}
+
+#[aoc(day17, part2)]
+pub fn solve_day15_part2(input: &ProgramImage) -> Result<i64, Box<dyn Error>> {
+    let map = get_map(input);
+    let routines = plan_routines(&map)?;
+    let outputs = run_robot(input, &routines, false);
+
+    Ok(*outputs.last().unwrap())
+}
+
+/// The lines the robot prints between frames to ask for its routines.
+const PROMPTS: [&str; 5] = [
+    "Main:",
+    "Function A:",
+    "Function B:",
+    "Function C:",
+    "Continuous video feed?",
+];
+
+/// Splits the robot's output into the camera frames it prints, one blank line apart. The
+/// prompts in between and the dust count at the end are skipped; any other chunk that isn't a
+/// map is an error.
+fn split_frames(output: &[i64]) -> Result<Vec<Grid<Tile>>, Box<dyn Error>> {
+    let text: String = output
+        .iter()
+        .take_while(|&&c| (0..128).contains(&c))
+        .map(|&c| c as u8 as char)
+        .collect();
+    let mut frames = vec![];
+    for chunk in text.split("\n\n") {
+        if chunk
+            .lines()
+            .all(|l| l.trim().is_empty() || PROMPTS.contains(&l))
+        {
+            continue;
+        }
+        let frame = parse_map(chunk).map_err(|e| format!("frame {}: {}", frames.len(), e))?;
+        frames.push(frame);
+    }
+    Ok(frames)
+}
+
+/// Where the frames of the robot's continuous video feed go.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VideoFeed {
+    /// Draw each frame over the last one, pausing `delay` between frames.
+    Terminal { delay: Duration },
+    /// Save each frame to `dir` as `frame_0000.ppm`, `frame_0001.ppm`... with every cell
+    /// `scale` pixels square.
+    Images { dir: PathBuf, scale: usize },
+}
+
+fn video_palette() -> Palette<Tile> {
+    let robot = Rgb(255, 200, 0);
+    Heading::ALL.iter().fold(
+        Palette::new('?', Rgb::BLACK)
+            .with(Tile::Empty, '.', Rgb(40, 40, 40))
+            .with(Tile::Scaffold, '#', Rgb(160, 160, 160))
+            .with(Tile::Tumbling, 'X', Rgb(255, 0, 0)),
+        |palette, &h| palette.with(Tile::Robot(h), h.to_arrow(), robot),
+    )
+}
+
+/// What watching the video feed produced.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Recording {
+    /// How many frames were sent to the feed.
+    pub frames: usize,
+    pub dust: i64,
+}
+
+/// Runs part 2 with the continuous video feed on, sending every frame to `feed`.
+pub fn watch_day17(input: &ProgramImage, feed: &VideoFeed) -> Result<Recording, Box<dyn Error>> {
+    let routines = plan_routines(&get_map(input))?;
+    let outputs = run_robot(input, &routines, true);
+    let palette = video_palette();
+    let frames = split_frames(&outputs)?;
+
+    for (idx, frame) in frames.iter().enumerate() {
+        match feed {
+            VideoFeed::Terminal { delay } => {
+                // Clear the screen and draw from the top-left corner.
+                print!("\x1b[2J\x1b[H{}", render_text(frame, &palette, true));
+                std::thread::sleep(*delay);
+            }
+            VideoFeed::Images { dir, scale } => {
+                save_image(
+                    frame,
+                    &palette,
+                    *scale,
+                    dir.join(format!("frame_{:04}.ppm", idx)),
+                )?;
+            }
+        }
+    }
+
+    Ok(Recording {
+        frames: frames.len(),
+        dust: *outputs.last().ok_or("The robot printed nothing")?,
+    })
+}
+
+#[test]
+fn test_day17_simulate() {
+    let map: Grid<Tile> = parse_map(
+        "#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#...#......
+....#...#......
+....#...#......
+....#####......",
+    )
+    .unwrap();
+
+    let routines: Routines = "A,B,C,B,A,C\nR,8,R,8\nR,4,R,4,R,8\nL,6,L,2"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        simulate(&map, &routines),
+        Simulation {
+            visited_all: true,
+            fell_off: None,
+            position: Point::new(0, 2),
+            heading: Heading::South,
+        }
+    );
+
+    // Stopping early leaves part of the scaffold unvisited.
+    let short: Routines = "A,B\nR,8,R,8\nR,4,R,4,R,8".parse().unwrap();
+    let sim = simulate(&map, &short);
+    assert!(!sim.visited_all);
+    assert_eq!(sim.fell_off, None);
+
+    // One step too far runs off the end of the first straight.
+    let overshoot: Routines = "A\nR,9".parse().unwrap();
+    assert_eq!(
+        simulate(&map, &overshoot),
+        Simulation {
+            visited_all: false,
+            fell_off: Some(Point::new(9, 6)),
+            position: Point::new(8, 6),
+            heading: Heading::East,
+        }
+    );
+}
+
+#[test]
+fn test_day17_split_frames() {
+    let stream =
+        "..#\n.^#\n\nMain:\nFunction A:\n\nContinuous video feed?\n\n..#\n.>#\n\n..#\n..X\n\n";
+    let mut output: Vec<i64> = stream.chars().map(|c| c as i64).collect();
+    output.push(1234);
+
+    let frames = split_frames(&output).unwrap();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0][Point::new(1, 1)], Tile::Robot(Heading::North));
+    assert_eq!(frames[1][Point::new(1, 1)], Tile::Robot(Heading::East));
+    assert_eq!(frames[2][Point::new(2, 1)], Tile::Tumbling);
+
+    // A garbled frame is reported rather than dropped.
+    let stream = "..#\n.^#\n\nMain:\n\n..#\n.%#\n\n";
+    let output: Vec<i64> = stream.chars().map(|c| c as i64).collect();
+    let err = split_frames(&output).unwrap_err();
+    assert!(err.to_string().starts_with("frame 1: "), "{}", err);
+}
 
-    *program.outputs.last().unwrap()
+#[test]
+fn test_day17_simulate_bad_routine() {
+    use Turn::*;
+
+    let map: Grid<Tile> = parse_map(
+        "#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#...#......
+....#...#......
+....#...#......
+....#####......",
+    )
+    .unwrap();
+
+    // The published path with the first `L,6` turned the wrong way. It still compresses, but
+    // the robot runs off the scaffold.
+    let path: Vec<Move> = [
+        (Right, 8),
+        (Right, 8),
+        (Right, 4),
+        (Right, 4),
+        (Right, 8),
+        (Right, 6),
+        (Left, 2),
+        (Right, 4),
+        (Right, 4),
+        (Right, 8),
+        (Right, 8),
+        (Right, 8),
+        (Left, 6),
+        (Left, 2),
+    ]
+    .iter()
+    .flat_map(|&(t, n)| vec![Move::Turn(t), Move::Forward(n)])
+    .collect();
+    let routines = Compressor::new().compress(&path).unwrap();
+
+    let sim = simulate(&map, &routines);
+    assert!(!sim.visited_all);
+    assert_eq!(sim.fell_off, Some(Point::new(12, 11)));
 }
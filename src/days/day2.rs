@@ -1,5 +1,7 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use std::error::Error;
+
 use crate::shared::*;
 
 // ======================================================
@@ -7,16 +9,13 @@ use crate::shared::*;
 // ======================================================
 
 #[aoc_generator(day2)]
-pub fn input_generator_day2(input: &str) -> Vec<i64> {
-    input
-        .split(',')
-        .map(|x| x.trim().parse().unwrap())
-        .collect()
+pub fn input_generator_day2(input: &str) -> Result<ProgramImage, ParseError> {
+    parse_program(input)
 }
 
 #[aoc(day2, part1)]
-pub fn solve_day2_part1(input: &[i64]) -> i64 {
-    let mut program = Program::new(input, &[]);
+pub fn solve_day2_part1(input: &ProgramImage) -> i64 {
+    let mut program = Program::from_image(input, &[]);
 
     // Restore the gravity assist program
     // Replace position 1 with the value 12
@@ -30,17 +29,15 @@ pub fn solve_day2_part1(input: &[i64]) -> i64 {
 }
 
 #[aoc(day2, part2)]
-pub fn solve_day2_part2(input: &[i64]) -> i64 {
-    for noun in 0..100 {
-        for verb in 0..100 {
-            let mut program = Program::new(input, &[]);
-            program[1] = noun;
-            program[2] = verb;
-            program.run();
-            if program[0] == 19_690_720 {
-                return 100 * noun + verb;
-            }
-        }
-    }
-    panic!("No noun/verb combination found!");
+pub fn solve_day2_part2(input: &ProgramImage) -> Result<i64, Box<dyn Error>> {
+    // Treat the noun and verb as symbols and solve for them instead of re-running the computer
+    let res = solve_for(
+        input,
+        &[],
+        &[(Unknown::Memory(1), 0..=99), (Unknown::Memory(2), 0..=99)],
+        Target::Memory(0),
+        19_690_720,
+    )
+    .ok_or("No noun/verb combination found!")?;
+    Ok(100 * res[0] + res[1])
 }
@@ -1,6 +1,5 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
 
 use crate::helper::*;
 
@@ -9,15 +8,7 @@ use crate::helper::*;
 // ======================================================
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum Direction {
-    Left,
-    Right,
-    Up,
-    Down,
-}
-
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct PathComponent(Direction, usize);
+pub struct PathComponent(Heading, usize);
 
 #[aoc_generator(day3)]
 pub fn input_generator_day3(input: &str) -> (Vec<PathComponent>, Vec<PathComponent>) {
@@ -26,15 +17,13 @@ pub fn input_generator_day3(input: &str) -> (Vec<PathComponent>, Vec<PathCompone
         .map(|line| {
             line.split(',')
                 .map(|rec| {
-                    let mut dir = rec.to_owned();
-                    let amount = dir.split_off(1).parse().unwrap();
-                    let d = match dir.as_str() {
-                        "L" => Direction::Left,
-                        "R" => Direction::Right,
-                        "U" => Direction::Up,
-                        "D" => Direction::Down,
-                        _ => panic!("Unexpected direction"),
-                    };
+                    let (dir, amount) = rec.split_at(1);
+                    let d = dir
+                        .chars()
+                        .next()
+                        .and_then(Heading::from_char)
+                        .expect("Unexpected direction");
+                    let amount = amount.parse().unwrap();
                     PathComponent(d, amount)
                 })
                 .collect_vec()
@@ -43,94 +32,32 @@ pub fn input_generator_day3(input: &str) -> (Vec<PathComponent>, Vec<PathCompone
     (inp[0].clone(), inp[1].clone())
 }
 
-fn delta_for_direction(dir: Direction) -> Point {
-    match dir {
-        Direction::Left => Point::new(-1, 0),
-        Direction::Right => Point::new(1, 0),
-        Direction::Up => Point::new(0, -1),
-        Direction::Down => Point::new(0, 1),
-    }
+fn wire_crossings(input: &(Vec<PathComponent>, Vec<PathComponent>)) -> Vec<Crossing> {
+    let wire = |path: &[PathComponent]| {
+        Wire::from_moves(
+            Point::new(0, 0),
+            path.iter().map(|&PathComponent(h, n)| (h, n)),
+        )
+    };
+    crossings(&[wire(&input.0), wire(&input.1)])
 }
 
 #[aoc(day3, part1)]
 pub fn solve_day3_part1(input: &(Vec<PathComponent>, Vec<PathComponent>)) -> u64 {
-    let mut closest = std::u64::MAX;
-
-    let path1 = &input.0;
-    let path2 = &input.1;
-
-    let mut positions: HashSet<Point> = HashSet::default();
-
-    // First wire
-    {
-        let mut current_position = Point::new(0, 0);
-        for movement in path1.iter() {
-            for _ in 0..movement.1 {
-                current_position += delta_for_direction(movement.0);
-                positions.insert(current_position);
-            }
-        }
-    }
-    // Second wire - check for intersections
-    {
-        let mut current_position = Point::new(0, 0);
-        for movement in path2.iter() {
-            for _ in 0..movement.1 {
-                current_position += delta_for_direction(movement.0);
-                if positions.contains(&current_position) {
-                    // We have an intersection; calculate the manhattan distance and
-                    // store if this is our closest intersection yet
-                    let md = (current_position.manhattan()) as u64;
-                    closest = std::cmp::min(md, closest);
-                }
-            }
-        }
-    }
-
-    closest
+    wire_crossings(input)
+        .iter()
+        .map(|c| c.point.manhattan() as u64)
+        .min()
+        .unwrap_or(u64::MAX)
 }
 
 #[aoc(day3, part2)]
 pub fn solve_day3_part2(input: &(Vec<PathComponent>, Vec<PathComponent>)) -> u64 {
-    let mut closest = std::u64::MAX;
-
-    let path1 = &input.0;
-    let path2 = &input.1;
-
-    let mut positions: HashMap<Point, u64> = HashMap::default();
-
-    // First wire
-    {
-        let mut current_position = Point::new(0, 0);
-        let mut total_movement = 0;
-        for movement in path1.iter() {
-            for _ in 0..movement.1 {
-                current_position += delta_for_direction(movement.0);
-                total_movement += 1;
-                positions.insert(current_position, total_movement);
-            }
-        }
-    }
-    // Second wire - check for intersections
-    {
-        let mut current_position = Point::new(0, 0);
-        let mut total_movement = 0;
-        for movement in path2.iter() {
-            for _ in 0..movement.1 {
-                current_position += delta_for_direction(movement.0);
-                total_movement += 1;
-
-                if positions.contains_key(&current_position) {
-                    // We have an intersection; calculate the total movement and
-                    // store if this is our closest intersection yet
-                    let both_movement = total_movement + positions[&current_position];
-                    closest = std::cmp::min(both_movement, closest);
-                }
-            }
-        }
-    }
-
-    closest
+    wire_crossings(input)
+        .iter()
+        .map(|c| (c.steps.0 + c.steps.1) as u64)
+        .min()
+        .unwrap_or(u64::MAX)
 }
 
 #[test]
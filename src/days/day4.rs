@@ -28,13 +28,13 @@ fn is_valid(digits: &[usize], part2: bool) -> bool {
 
 #[test]
 fn test_day4_valid() {
-    assert_eq!(is_valid(&[1, 1, 1, 1, 1, 1], true), false);
-    assert_eq!(is_valid(&[1, 1, 1, 1, 1, 1], false), true);
-    assert_eq!(is_valid(&[1, 1, 2, 2, 2, 2], true), true);
-    assert_eq!(is_valid(&[1, 1, 2, 2, 2, 2], false), true);
-    assert_eq!(is_valid(&[1, 2, 3, 4, 5, 6], true), false);
-    assert_eq!(is_valid(&[1, 2, 3, 4, 5, 6], false), false);
-    assert_eq!(is_valid(&[1, 2, 3, 4, 5, 5], true), true);
+    assert!(!is_valid(&[1, 1, 1, 1, 1, 1], true));
+    assert!(is_valid(&[1, 1, 1, 1, 1, 1], false));
+    assert!(is_valid(&[1, 1, 2, 2, 2, 2], true));
+    assert!(is_valid(&[1, 1, 2, 2, 2, 2], false));
+    assert!(!is_valid(&[1, 2, 3, 4, 5, 6], true));
+    assert!(!is_valid(&[1, 2, 3, 4, 5, 6], false));
+    assert!(is_valid(&[1, 2, 3, 4, 5, 5], true));
 }
 
 fn get_num(digits: &[usize]) -> usize {
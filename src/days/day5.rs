@@ -7,16 +7,13 @@ use crate::shared::*;
 // ======================================================
 
 #[aoc_generator(day5)]
-pub fn input_generator_day5(input: &str) -> Vec<i64> {
-    input
-        .split(',')
-        .map(|x| x.trim().parse().unwrap())
-        .collect()
+pub fn input_generator_day5(input: &str) -> Result<ProgramImage, ParseError> {
+    parse_program(input)
 }
 
 #[aoc(day5, part1)]
-pub fn solve_day5_part1(input: &[i64]) -> i64 {
-    let mut program = Program::new(input, &[1]);
+pub fn solve_day5_part1(input: &ProgramImage) -> i64 {
+    let mut program = Program::from_image(input, &[1]);
 
     program.run();
 
@@ -24,8 +21,8 @@ pub fn solve_day5_part1(input: &[i64]) -> i64 {
 }
 
 #[aoc(day5, part2)]
-pub fn solve_day5_part2(input: &[i64]) -> i64 {
-    let mut program = Program::new(input, &[5]);
+pub fn solve_day5_part2(input: &ProgramImage) -> i64 {
+    let mut program = Program::from_image(input, &[5]);
 
     program.run();
 
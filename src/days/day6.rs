@@ -1,8 +1,10 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
-use id_arena::{Arena, Id};
 use std::collections::HashMap;
+use std::error::Error;
+
+use crate::helper::bfs;
 
 // ======================================================
 // DAY 6
@@ -22,142 +24,96 @@ pub fn input_generator_day6(input: &str) -> Vec<(String, String)> {
         .collect_vec()
 }
 
-type OrbitId = Id<Orbit>;
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct Orbit {
-    pub id: OrbitId,
-    pub text_id: String,
-    pub orbits: Option<OrbitId>,
-
-    dist_com: isize,
-    dist_you: isize,
+/// The orbit map, indexed both ways.
+struct Orbits<'a> {
+    /// The bodies directly orbiting each body.
+    satellites: HashMap<&'a str, Vec<&'a str>>,
+    /// The body each body directly orbits.
+    centres: HashMap<&'a str, &'a str>,
 }
 
-impl Orbit {
-    pub fn new(id: OrbitId, text_id: String) -> Self {
-        Orbit {
-            id,
-            text_id,
-            orbits: None,
-
-            dist_com: -1,
-            dist_you: -1,
+impl<'a> Orbits<'a> {
+    fn new(input: &'a [(String, String)]) -> Self {
+        let mut satellites: HashMap<&str, Vec<&str>> = HashMap::default();
+        let mut centres = HashMap::default();
+        for (centre, satellite) in input.iter() {
+            satellites.entry(centre).or_default().push(satellite);
+            centres.insert(satellite.as_str(), centre.as_str());
+        }
+        Orbits {
+            satellites,
+            centres,
         }
     }
 
-    pub fn set_orbit_count(id: OrbitId, arena: &mut Arena<Orbit>) {
-        {
-            let node = arena.get_mut(id).unwrap();
-            if let Some(parent) = node.orbits {
-                let parent_node = arena.get(parent).unwrap();
-                if parent_node.dist_com == -1 {
-                    Orbit::set_orbit_count(parent, arena);
-                }
-            }
-        }
-        {
-            let parent = {
-                let node = arena.get_mut(id).unwrap();
-                node.orbits
-            };
-            let parent_node = parent.map(|p| arena.get(p).unwrap());
-            let dist = parent_node.map_or(0, |p| 1 + p.dist_com);
-            let mut node = arena.get_mut(id).unwrap();
-            node.dist_com = dist;
-        }
+    fn satellites(&self, body: &str) -> Vec<&'a str> {
+        self.satellites.get(body).cloned().unwrap_or_default()
     }
 
-    pub fn orbit_count(&self) -> usize {
-        self.dist_com as usize
+    /// The bodies one orbital transfer away: the satellites and the centre.
+    fn neighbours(&self, body: &str) -> Vec<&'a str> {
+        let mut res = self.satellites(body);
+        res.extend(self.centres.get(body));
+        res
     }
 }
 
 #[aoc(day6, part1)]
 pub fn solve_day6_part1(input: &[(String, String)]) -> usize {
-    let ids = {
-        let id1_iter = input.iter().map(|pair| &pair.0);
-        let id2_iter = input.iter().map(|pair| &pair.1);
-        id1_iter.chain(id2_iter).unique().collect_vec()
-    };
-    let mut arena = Arena::<Orbit>::new();
-
-    let mut orbits: HashMap<String, OrbitId> = HashMap::default();
-    for &id in ids.iter() {
-        let ob = arena.alloc_with_id(|arena_id| Orbit::new(arena_id, id.to_owned()));
-        orbits.insert(id.to_owned(), ob);
-    }
+    let orbits = Orbits::new(input);
+    let depths = bfs(
+        std::iter::once("com"),
+        |body| orbits.satellites(body),
+        |_| false,
+    );
+
+    depths.iter().map(|(_, depth)| depth).sum()
+}
 
-    for orbit in input.iter() {
-        let pa_id = orbits[&orbit.0];
-        let ob_id = orbits[&orbit.1];
-        let mut ob = arena.get_mut(ob_id).unwrap();
-        ob.orbits = Some(pa_id);
-    }
+#[aoc(day6, part2)]
+pub fn solve_day6_part2(input: &[(String, String)]) -> Result<usize, Box<dyn Error>> {
+    let orbits = Orbits::new(input);
+    let from = *orbits
+        .centres
+        .get("you")
+        .ok_or("YOU isn't orbiting anything")?;
+    let to = *orbits
+        .centres
+        .get("san")
+        .ok_or("SAN isn't orbiting anything")?;
+
+    let transfers = bfs(
+        std::iter::once(from),
+        |body| orbits.neighbours(body),
+        |body| body == to,
+    );
+    Ok(transfers.cost(to).ok_or("No route to Santa")?)
+}
 
-    ids.iter()
-        .for_each(|id| Orbit::set_orbit_count(orbits[id.to_owned()], &mut arena));
+#[test]
+fn test_day6_p1_ex1() {
+    let input = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
+    let gen = input_generator_day6(input);
+    let res = solve_day6_part1(&gen);
 
-    ids.iter()
-        .map(|id| arena.get(orbits[id.to_owned()]).unwrap().orbit_count())
-        .sum()
+    assert_eq!(res, 42);
 }
 
-#[aoc(day6, part2)]
-pub fn solve_day6_part2(input: &[(String, String)]) -> usize {
-    let ids = {
-        let id1_iter = input.iter().map(|pair| &pair.0);
-        let id2_iter = input.iter().map(|pair| &pair.1);
-        id1_iter.chain(id2_iter).unique().collect_vec()
-    };
-    let mut arena = Arena::<Orbit>::new();
-
-    let mut orbits: HashMap<String, OrbitId> = HashMap::default();
-    for &id in ids.iter() {
-        let ob = arena.alloc_with_id(|arena_id| Orbit::new(arena_id, id.to_owned()));
-        orbits.insert(id.to_owned(), ob);
-    }
+#[test]
+fn test_day6_p2_ex1() {
+    let input = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN";
+    let gen = input_generator_day6(input);
+    let res = solve_day6_part2(&gen).unwrap();
 
-    for orbit in input.iter() {
-        let pa_id = orbits[&orbit.0];
-        let ob_id = orbits[&orbit.1];
-        let mut ob = arena.get_mut(ob_id).unwrap();
-        ob.orbits = Some(pa_id);
-    }
+    assert_eq!(res, 4);
+}
 
-    // Trace up from "you"
-    {
-        let mut counter = 0;
-        let node_id = arena.get(orbits["you"]).unwrap().orbits.unwrap();
-        let mut node = arena.get_mut(node_id).unwrap();
-        loop {
-            node.dist_you = counter;
-            if let Some(p_id) = node.orbits {
-                node = arena.get_mut(p_id).unwrap();
-                counter += 1;
-            } else {
-                break;
-            }
-        }
-    }
-    // Trace up from "san"
-    {
-        let mut counter = 0;
-        let node_id = arena.get(orbits["san"]).unwrap().orbits.unwrap();
-        let mut node = arena.get_mut(node_id).unwrap();
-        loop {
-            // Check if we've already reached "you"
-            if node.dist_you >= 0 {
-                return counter + node.dist_you as usize;
-            }
-            if let Some(p_id) = node.orbits {
-                node = arena.get_mut(p_id).unwrap();
-                counter += 1;
-            } else {
-                break;
-            }
-        }
-    }
+#[test]
+fn test_day6_p2_missing() {
+    // Part 1's example has neither YOU nor SAN.
+    let input = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
+    let gen = input_generator_day6(input);
+    let res = solve_day6_part2(&gen);
 
-    panic!("Something went wrong!");
+    assert_eq!(res.unwrap_err().to_string(), "YOU isn't orbiting anything");
 }
@@ -8,22 +8,19 @@ use crate::shared::*;
 // ======================================================
 
 #[aoc_generator(day7)]
-pub fn input_generator_day7(input: &str) -> Vec<i64> {
-    input
-        .split(',')
-        .map(|x| x.trim().parse().unwrap())
-        .collect()
+pub fn input_generator_day7(input: &str) -> Result<ProgramImage, ParseError> {
+    parse_program(input)
 }
 
 #[aoc(day7, part1)]
-pub fn solve_day7_part1(input: &[i64]) -> i64 {
+pub fn solve_day7_part1(input: &ProgramImage) -> i64 {
     let phase_settings = 0..5;
-    let mut max_result = std::i64::MIN;
+    let mut max_result = i64::MIN;
     for phases in phase_settings.permutations(5) {
         let mut result = 0;
 
         for phase in phases {
-            let mut program = Program::new(input, &[phase, result]);
+            let mut program = Program::from_image(input, &[phase, result]);
 
             program.run();
 
@@ -37,15 +34,15 @@ pub fn solve_day7_part1(input: &[i64]) -> i64 {
 }
 
 #[aoc(day7, part2)]
-pub fn solve_day7_part2(input: &[i64]) -> i64 {
+pub fn solve_day7_part2(input: &ProgramImage) -> i64 {
     let phase_settings = 5..10;
-    let mut max_result = std::i64::MIN;
+    let mut max_result = i64::MIN;
     for phases in phase_settings.permutations(5) {
         let mut result = 0;
 
         let mut programs = phases
             .iter()
-            .map(|&phase| Program::new(input, &[phase]))
+            .map(|&phase| Program::from_image(input, &[phase]))
             .collect_vec();
 
         loop {
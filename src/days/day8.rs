@@ -1,3 +1,4 @@
+use crate::helper::{ocr_buffer, OcrError};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
@@ -49,7 +50,7 @@ pub fn solve_day8_part1(input: &[u8]) -> usize {
 }
 
 #[aoc(day8, part2)]
-pub fn solve_day8_part2(input: &[u8]) -> String {
+pub fn solve_day8_part2(input: &[u8]) -> Result<String, OcrError> {
     const WIDTH: usize = 25;
     const HEIGHT: usize = 6;
 
@@ -79,18 +80,5 @@ pub fn solve_day8_part2(input: &[u8]) -> String {
         }
     }
 
-    let mut printable = String::from("\n");
-    for y in 0..HEIGHT {
-        for x in 0..WIDTH {
-            let idx = y * WIDTH + x;
-            if res[idx] == 0 {
-                printable.push(' ');
-            } else {
-                printable.push('O');
-            }
-        }
-        printable.push('\n');
-    }
-
-    printable
+    ocr_buffer(&res, WIDTH, 1)
 }
@@ -7,16 +7,13 @@ use crate::shared::*;
 // ======================================================
 
 #[aoc_generator(day9)]
-pub fn input_generator_day9(input: &str) -> Vec<i64> {
-    input
-        .split(',')
-        .map(|x| x.trim().parse().unwrap())
-        .collect()
+pub fn input_generator_day9(input: &str) -> Result<ProgramImage, ParseError> {
+    parse_program(input)
 }
 
 #[aoc(day9, part1)]
-pub fn solve_day9_part1(input: &[i64]) -> String {
-    let mut program = Program::new(input, &[1]);
+pub fn solve_day9_part1(input: &ProgramImage) -> String {
+    let mut program = Program::from_image(input, &[1]);
 
     program.run();
 
@@ -24,8 +21,8 @@ pub fn solve_day9_part1(input: &[i64]) -> String {
 }
 
 #[aoc(day9, part2)]
-pub fn solve_day9_part2(input: &[i64]) -> String {
-    let mut program = Program::new(input, &[2]);
+pub fn solve_day9_part2(input: &ProgramImage) -> String {
+    let mut program = Program::from_image(input, &[2]);
 
     program.run();
 
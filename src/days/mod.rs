@@ -2,11 +2,11 @@ mod day1;
 mod day10;
 mod day11;
 mod day12;
-mod day13;
+pub mod day13;
 mod day14;
-mod day15;
+pub mod day15;
 mod day16;
-mod day17;
+pub mod day17;
 mod day2;
 mod day3;
 mod day4;
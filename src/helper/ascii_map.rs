@@ -0,0 +1,166 @@
+use crate::helper::{Grid, Point, Rect};
+
+/// A tile that can be read from and written back to a character map.
+pub trait TileChar: Sized {
+    fn from_char(ch: char) -> Option<Self>;
+    fn to_char(&self) -> char;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapParseError {
+    /// No tile maps to `ch`. Lines and columns start at 1.
+    UnknownTile {
+        line: usize,
+        column: usize,
+        ch: char,
+    },
+    /// Line `line` is `found` characters wide, but earlier lines were `expected` wide.
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for MapParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapParseError::UnknownTile { line, column, ch } => write!(
+                f,
+                "unknown tile {:?} at line {}, column {}",
+                ch, line, column
+            ),
+            MapParseError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} is {} characters wide, expected {}",
+                line, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MapParseError {}
+
+/// Parses a rectangular character map into a dense grid with its top-left cell at `(0, 0)`.
+/// Trailing blank lines are ignored.
+pub fn parse_map<T: TileChar>(input: &str) -> Result<Grid<T>, MapParseError> {
+    let lines: Vec<&str> = input.lines().map(|l| l.trim_end_matches('\r')).collect();
+    let count = lines.len() - lines.iter().rev().take_while(|l| l.is_empty()).count();
+
+    let mut cells = vec![];
+    let mut width = None;
+    for (y, line) in lines[..count].iter().enumerate() {
+        let row_start = cells.len();
+        for (x, ch) in line.chars().enumerate() {
+            let tile = T::from_char(ch).ok_or(MapParseError::UnknownTile {
+                line: y + 1,
+                column: x + 1,
+                ch,
+            })?;
+            cells.push(tile);
+        }
+        let found = cells.len() - row_start;
+        match width {
+            None => width = Some(found),
+            Some(expected) if expected != found => {
+                return Err(MapParseError::RaggedRow {
+                    line: y + 1,
+                    expected,
+                    found,
+                })
+            }
+            _ => (),
+        }
+    }
+
+    Ok(Grid::from_vec(width.unwrap_or(0), cells))
+}
+
+/// Parses a map printed by an Intcode program as a stream of ASCII codes.
+pub fn parse_ascii_output<T: TileChar>(output: &[i64]) -> Result<Grid<T>, MapParseError> {
+    let text: String = output
+        .iter()
+        .map(|&c| {
+            std::char::from_u32(c as u32)
+                .filter(|_| (0..128).contains(&c))
+                .unwrap_or(std::char::REPLACEMENT_CHARACTER)
+        })
+        .collect();
+    parse_map(&text)
+}
+
+/// Writes the grid back out as a character map, one line per row.
+pub fn map_to_string<T: TileChar>(grid: &Grid<T>) -> String {
+    let mut res = String::new();
+    if let Some(Rect { min, max }) = grid.bounds() {
+        for y in min.y()..=max.y() {
+            for x in min.x()..=max.x() {
+                res.push(grid[Point::new(x, y)].to_char());
+            }
+            res.push('\n');
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TestTile {
+    Open,
+    Wall,
+}
+
+#[cfg(test)]
+impl TileChar for TestTile {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            '.' => Some(TestTile::Open),
+            '#' => Some(TestTile::Wall),
+            _ => None,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            TestTile::Open => '.',
+            TestTile::Wall => '#',
+        }
+    }
+}
+
+#[test]
+fn test_parse_map() {
+    let map = "#..\n.#.\n..#\n\n";
+    let grid: Grid<TestTile> = parse_map(map).unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 3));
+    assert_eq!(grid[Point::new(1, 1)], TestTile::Wall);
+    assert_eq!(grid[Point::new(2, 1)], TestTile::Open);
+    assert_eq!(map_to_string(&grid), "#..\n.#.\n..#\n");
+
+    let ascii = map.chars().map(|c| c as i64).collect::<Vec<_>>();
+    assert_eq!(parse_ascii_output::<TestTile>(&ascii).unwrap(), grid);
+}
+
+#[test]
+fn test_parse_map_errors() {
+    assert_eq!(
+        parse_map::<TestTile>("#..\n.x.\n").unwrap_err(),
+        MapParseError::UnknownTile {
+            line: 2,
+            column: 2,
+            ch: 'x'
+        }
+    );
+    assert_eq!(
+        parse_map::<TestTile>("#..\n.#\n").unwrap_err(),
+        MapParseError::RaggedRow {
+            line: 2,
+            expected: 3,
+            found: 2
+        }
+    );
+}
@@ -0,0 +1,168 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use crate::helper::Point;
+
+/// A direction on the grid, reduced so that every point along the same ray shares one
+/// bearing. Bearings are ordered clockwise starting from straight up (`y` points down).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bearing(Point);
+
+impl Bearing {
+    /// The bearing of `delta`, or `None` for the zero vector.
+    pub fn new(delta: Point) -> Option<Self> {
+        if delta == Point::zero() {
+            None
+        } else {
+            Some(Bearing(delta.reduced()))
+        }
+    }
+
+    /// The smallest step along this bearing.
+    pub fn step(&self) -> Point {
+        self.0
+    }
+
+    /// Which quarter turn clockwise from up the bearing lies in. Each quadrant includes its
+    /// starting axis.
+    fn quadrant(&self) -> u8 {
+        let (x, y) = (self.0.x(), self.0.y());
+        if x >= 0 && y < 0 {
+            0
+        } else if x > 0 && y >= 0 {
+            1
+        } else if x <= 0 && y > 0 {
+            2
+        } else {
+            3
+        }
+    }
+}
+
+impl Ord for Bearing {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Within a quadrant the bearings are less than a half turn apart, so the sign of the
+        // cross product says which comes first.
+        self.quadrant()
+            .cmp(&other.quadrant())
+            .then_with(|| 0.cmp(&self.0.cross(&other.0)))
+    }
+}
+
+impl PartialOrd for Bearing {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsteroidField {
+    asteroids: Vec<Point>,
+}
+
+impl AsteroidField {
+    pub fn new(asteroids: Vec<Point>) -> Self {
+        AsteroidField { asteroids }
+    }
+
+    pub fn asteroids(&self) -> &[Point] {
+        &self.asteroids
+    }
+
+    /// How many other asteroids can be seen from `base`: one per distinct bearing.
+    pub fn visible_from(&self, base: Point) -> usize {
+        self.asteroids
+            .iter()
+            .filter_map(|&a| Bearing::new(a - base))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// The asteroid that can see the most others, and how many it sees.
+    pub fn best_station(&self) -> Option<(Point, usize)> {
+        self.asteroids
+            .iter()
+            .map(|&a| (a, self.visible_from(a)))
+            .fold(None, |best, (a, n)| match best {
+                Some((_, m)) if m >= n => best,
+                _ => Some((a, n)),
+            })
+    }
+
+    /// Every other asteroid, grouped by bearing from `base` and nearest first.
+    pub fn lines_of_sight(&self, base: Point) -> BTreeMap<Bearing, Vec<Point>> {
+        let mut lines: BTreeMap<Bearing, Vec<Point>> = BTreeMap::new();
+        for &a in self.asteroids.iter() {
+            if let Some(bearing) = Bearing::new(a - base) {
+                lines.entry(bearing).or_default().push(a);
+            }
+        }
+        for line in lines.values_mut() {
+            line.sort_by_key(|&a| (a - base).manhattan());
+        }
+        lines
+    }
+
+    /// The order a laser at `base` destroys every other asteroid, starting pointing up and
+    /// rotating clockwise, hitting only the nearest asteroid on each bearing per rotation.
+    pub fn vaporization_order(&self, base: Point) -> Vaporization {
+        let lines: Vec<VecDeque<Point>> = self
+            .lines_of_sight(base)
+            .into_values()
+            .map(VecDeque::from)
+            .collect();
+        Vaporization { lines, idx: 0 }
+    }
+}
+
+pub struct Vaporization {
+    lines: Vec<VecDeque<Point>>,
+    idx: usize,
+}
+
+impl Iterator for Vaporization {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        self.idx %= self.lines.len();
+        let hit = self.lines[self.idx].pop_front();
+        // Cleared lines are dropped, which leaves `idx` on the next bearing already.
+        if self.lines[self.idx].is_empty() {
+            self.lines.remove(self.idx);
+        } else {
+            self.idx += 1;
+        }
+        hit
+    }
+}
+
+#[test]
+fn test_bearing_order() {
+    let clockwise = [
+        Point::new(0, -1),
+        Point::new(1, -3),
+        Point::new(1, -1),
+        Point::new(1, 0),
+        Point::new(2, 1),
+        Point::new(0, 1),
+        Point::new(-1, 1),
+        Point::new(-1, 0),
+        Point::new(-3, -1),
+    ];
+    let bearings: Vec<Bearing> = clockwise.iter().filter_map(|&p| Bearing::new(p)).collect();
+    for pair in bearings.windows(2) {
+        assert!(pair[0] < pair[1], "{:?}", pair);
+    }
+    assert_eq!(
+        Bearing::new(Point::new(4, -6)),
+        Bearing::new(Point::new(2, -3))
+    );
+    assert_ne!(
+        Bearing::new(Point::new(2, 0)),
+        Bearing::new(Point::new(-2, 0))
+    );
+    assert_eq!(Bearing::new(Point::new(0, 0)), None);
+}
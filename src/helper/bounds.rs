@@ -0,0 +1,166 @@
+use crate::helper::Vector;
+
+/// An axis-aligned box of integer points, with both corners inclusive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bounds<const N: usize> {
+    pub min: Vector<i64, N>,
+    pub max: Vector<i64, N>,
+}
+
+pub type Rect = Bounds<2>;
+pub type Cuboid = Bounds<3>;
+
+impl<const N: usize> Bounds<N> {
+    /// The smallest box containing both corners, whichever way round they are given.
+    pub fn new(a: Vector<i64, N>, b: Vector<i64, N>) -> Self {
+        Bounds {
+            min: a.min(&b),
+            max: a.max(&b),
+        }
+    }
+
+    /// The smallest box containing every point, or `None` if there are none.
+    pub fn from_points<I: IntoIterator<Item = Vector<i64, N>>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Bounds::new(first, first), |b, p| b.including(p)))
+    }
+
+    /// Grows the box just enough to contain `p`.
+    pub fn including(&self, p: Vector<i64, N>) -> Self {
+        Bounds {
+            min: self.min.min(&p),
+            max: self.max.max(&p),
+        }
+    }
+
+    pub fn contains(&self, p: Vector<i64, N>) -> bool {
+        (0..N).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = self.min.max(&other.min);
+        let max = self.max.min(&other.max);
+        if (0..N).all(|i| min[i] <= max[i]) {
+            Some(Bounds { min, max })
+        } else {
+            None
+        }
+    }
+
+    /// The smallest box containing both boxes.
+    pub fn union(&self, other: &Self) -> Self {
+        Bounds {
+            min: self.min.min(&other.min),
+            max: self.max.max(&other.max),
+        }
+    }
+
+    /// Grows the box by `by` on every side. A negative `by` shrinks it, and returns `None` if
+    /// nothing is left.
+    pub fn expand(&self, by: i64) -> Option<Self> {
+        let min = self.min.map(|c| c - by);
+        let max = self.max.map(|c| c + by);
+        if (0..N).all(|i| min[i] <= max[i]) {
+            Some(Bounds { min, max })
+        } else {
+            None
+        }
+    }
+
+    /// The number of points along each axis.
+    pub fn size(&self) -> Vector<i64, N> {
+        (self.max - self.min).map(|c| c + 1)
+    }
+
+    /// The number of points in the box.
+    pub fn volume(&self) -> i64 {
+        self.size().v.iter().product()
+    }
+
+    /// Every point in the box, with the first axis changing fastest (row-major for a `Rect`).
+    pub fn points(&self) -> impl Iterator<Item = Vector<i64, N>> {
+        let bounds = *self;
+        let mut next = Some(self.min);
+        std::iter::from_fn(move || {
+            let curr = next?;
+            let mut p = curr;
+            next = None;
+            for i in 0..N {
+                if p[i] < bounds.max[i] {
+                    p[i] += 1;
+                    next = Some(p);
+                    break;
+                }
+                p[i] = bounds.min[i];
+            }
+            Some(curr)
+        })
+    }
+}
+
+impl Rect {
+    pub fn width(&self) -> usize {
+        self.size().x() as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.size().y() as usize
+    }
+
+    pub fn area(&self) -> i64 {
+        self.volume()
+    }
+}
+
+#[test]
+fn test_rect() {
+    use crate::helper::Point;
+
+    let a = Rect::from_points(vec![Point::new(3, -1), Point::new(0, 2), Point::new(1, 1)]).unwrap();
+    assert_eq!(a, Rect::new(Point::new(3, 2), Point::new(0, -1)));
+    assert_eq!((a.width(), a.height(), a.area()), (4, 4, 16));
+    assert!(a.contains(Point::new(0, -1)));
+    assert!(!a.contains(Point::new(4, 0)));
+    assert_eq!(Rect::from_points(Vec::<Point>::new()), None);
+
+    let b = Rect::new(Point::new(2, 2), Point::new(5, 5));
+    assert_eq!(
+        a.intersection(&b),
+        Some(Rect::new(Point::new(2, 2), Point::new(3, 2)))
+    );
+    assert_eq!(a.union(&b), Rect::new(Point::new(0, -1), Point::new(5, 5)));
+    assert_eq!(
+        a.intersection(&Rect::new(Point::new(9, 9), Point::new(9, 9))),
+        None
+    );
+    assert_eq!(
+        b.expand(1),
+        Some(Rect::new(Point::new(1, 1), Point::new(6, 6)))
+    );
+    assert_eq!(b.expand(-2), None);
+
+    let points: Vec<Point> = Rect::new(Point::new(0, 0), Point::new(1, 1))
+        .points()
+        .collect();
+    assert_eq!(
+        points,
+        vec![
+            Point::new(0, 0),
+            Point::new(1, 0),
+            Point::new(0, 1),
+            Point::new(1, 1)
+        ]
+    );
+}
+
+#[test]
+fn test_cuboid() {
+    use crate::helper::Point3;
+
+    let c = Cuboid::new(Point3::new(0, 0, 0), Point3::new(1, 2, 3));
+    assert_eq!(c.volume(), 24);
+    assert_eq!(c.points().count(), 24);
+    assert!(c.points().all(|p| c.contains(p)));
+    assert_eq!(c.points().last(), Some(c.max));
+}
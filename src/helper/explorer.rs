@@ -0,0 +1,261 @@
+use std::iter::once;
+
+use crate::helper::{bfs, orthogonal, Grid, Heading, Point};
+
+/// What a droid reports after being told to move one step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Status<T> {
+    /// The droid moved onto a cell holding `T`.
+    Moved(T),
+    /// A wall was in the way, so the droid stayed put.
+    Blocked,
+    /// The droid no longer responds.
+    Stopped,
+}
+
+/// A remote-controlled droid that can only be told to step in a direction.
+pub trait Droid {
+    type Tile;
+
+    fn try_move(&mut self, heading: Heading) -> Status<Self::Tile>;
+}
+
+/// What is known about a cell of the explored map.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Cell<T> {
+    Unknown,
+    Wall,
+    Open(T),
+}
+
+impl<T> Cell<T> {
+    pub fn is_open(&self) -> bool {
+        matches!(self, Cell::Open(_))
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Cell::Unknown)
+    }
+}
+
+/// Maps out a maze by driving a droid around it, starting from `(0, 0)`.
+pub struct Explorer<D: Droid> {
+    droid: D,
+    map: Grid<Cell<D::Tile>>,
+    position: Point,
+    moves: usize,
+}
+
+impl<D: Droid> Explorer<D>
+where
+    D::Tile: Clone,
+{
+    /// Starts exploring with the droid standing on `start`.
+    pub fn new(droid: D, start: D::Tile) -> Self {
+        let mut map = Grid::sparse(Cell::Unknown);
+        map.set(Point::new(0, 0), Cell::Open(start));
+        Explorer {
+            droid,
+            map,
+            position: Point::new(0, 0),
+            moves: 0,
+        }
+    }
+
+    pub fn droid(&self) -> &D {
+        &self.droid
+    }
+
+    pub fn map(&self) -> &Grid<Cell<D::Tile>> {
+        &self.map
+    }
+
+    /// Where the droid is, as confirmed by its replies.
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    /// How many move commands the droid has been sent.
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    fn cell(&self, p: Point) -> &Cell<D::Tile> {
+        self.map.get(p).unwrap_or(&Cell::Unknown)
+    }
+
+    fn is_open(&self, p: Point) -> bool {
+        self.cell(p).is_open()
+    }
+
+    /// Unknown cells next to open ones: where exploring would go next. Once `explore` has
+    /// finished, these are the cells the droid couldn't reach.
+    pub fn frontier(&self) -> Vec<Point> {
+        let mut frontier: Vec<Point> = self
+            .map
+            .iter()
+            .filter(|(_, c)| c.is_open())
+            .flat_map(|(p, _)| orthogonal(p))
+            .filter(|&n| self.cell(n).is_unknown())
+            .collect();
+        frontier.sort_by_key(|p| (p.y(), p.x()));
+        frontier.dedup();
+        frontier
+    }
+
+    /// The shortest route over known open cells, including both ends.
+    pub fn path(&self, from: Point, to: Point) -> Option<Vec<Point>> {
+        bfs(
+            once(from),
+            |p| orthogonal(p).filter(move |&n| self.is_open(n)),
+            |p| p == to,
+        )
+        .goal_path()
+    }
+
+    /// Sends one move command and records what the droid found.
+    fn step(&mut self, heading: Heading) -> Status<()> {
+        self.moves += 1;
+        let target = self.position + heading;
+        match self.droid.try_move(heading) {
+            Status::Moved(tile) => {
+                self.map.set(target, Cell::Open(tile));
+                self.position = target;
+                Status::Moved(())
+            }
+            Status::Blocked => {
+                self.map.set(target, Cell::Wall);
+                Status::Blocked
+            }
+            Status::Stopped => Status::Stopped,
+        }
+    }
+
+    /// Drives the droid to `goal` along the shortest known route. Returns whether it got
+    /// there: a route can turn out to be blocked, or the droid can stop on the way.
+    pub fn walk_to(&mut self, goal: Point) -> bool {
+        self.walk(goal) == Status::Moved(())
+    }
+
+    fn walk(&mut self, goal: Point) -> Status<()> {
+        let path = match self.path(self.position, goal) {
+            Some(path) => path,
+            None => return Status::Blocked,
+        };
+        for next in path.into_iter().skip(1) {
+            let heading = Heading::from_delta(next - self.position).expect("Not a single step");
+            match self.step(heading) {
+                Status::Moved(()) => (),
+                status => return status,
+            }
+        }
+        Status::Moved(())
+    }
+
+    /// Explores until every cell reachable from the start is known or the droid stops,
+    /// always heading for the nearest unexplored cell rather than backtracking. Returns the
+    /// cells left unexplored.
+    pub fn explore(&mut self) -> Vec<Point> {
+        loop {
+            // The nearest open cell with an unknown neighbour.
+            let nearest = bfs(
+                once(self.position),
+                |p| {
+                    orthogonal(p)
+                        .filter(|&n| self.is_open(n))
+                        .collect::<Vec<_>>()
+                },
+                |p| orthogonal(p).any(|n| self.cell(n).is_unknown()),
+            )
+            .goal;
+            let target = match nearest {
+                Some(p) => p,
+                None => break,
+            };
+            match self.walk(target) {
+                Status::Moved(()) => (),
+                // The map has changed, so look again.
+                Status::Blocked => continue,
+                Status::Stopped => break,
+            }
+
+            let heading = Heading::compass()
+                .find(|&h| self.cell(target + h).is_unknown())
+                .expect("No unknown neighbour");
+            if self.step(heading) == Status::Stopped {
+                break;
+            }
+        }
+        self.frontier()
+    }
+}
+
+#[cfg(test)]
+struct MazeDroid {
+    maze: Grid<char>,
+    position: Point,
+    battery: usize,
+}
+
+#[cfg(test)]
+impl Droid for MazeDroid {
+    type Tile = char;
+
+    fn try_move(&mut self, heading: Heading) -> Status<char> {
+        if self.battery == 0 {
+            return Status::Stopped;
+        }
+        self.battery -= 1;
+        let target = self.position + heading;
+        match self.maze.get(target) {
+            Some('#') | None => Status::Blocked,
+            Some(&c) => {
+                self.position = target;
+                Status::Moved(c)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_explorer() {
+    let maze: Grid<char> = Grid::from_rows(
+        "#######\n#..#..#\n#.##.##\n#..O..#\n#######"
+            .lines()
+            .map(|l| l.chars().collect())
+            .collect(),
+    )
+    .unwrap();
+    let droid = MazeDroid {
+        maze: maze.clone().with_origin(Point::new(-1, -1)),
+        position: Point::new(0, 0),
+        battery: usize::MAX,
+    };
+
+    let mut explorer = Explorer::new(droid, '.');
+    assert_eq!(explorer.explore(), vec![]);
+    let open = explorer.map().iter().filter(|(_, c)| c.is_open()).count();
+    assert_eq!(open, maze.iter().filter(|&(_, &c)| c != '#').count());
+    assert_eq!(explorer.droid().position, explorer.position());
+
+    let oxygen = explorer.map().find(&Cell::Open('O')).next().unwrap();
+    assert_eq!(oxygen, Point::new(2, 2));
+    assert_eq!(
+        explorer.path(Point::new(0, 0), oxygen).map(|p| p.len()),
+        Some(5)
+    );
+
+    assert!(explorer.walk_to(Point::new(4, 0)));
+    assert_eq!(explorer.droid().position, Point::new(4, 0));
+
+    // A droid that gives up early leaves part of the maze unexplored.
+    let tired = MazeDroid {
+        maze: maze.with_origin(Point::new(-1, -1)),
+        position: Point::new(0, 0),
+        battery: 6,
+    };
+    let mut explorer = Explorer::new(tired, '.');
+    let frontier = explorer.explore();
+    assert!(!frontier.is_empty());
+    assert_eq!(explorer.moves(), 7);
+}
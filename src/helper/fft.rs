@@ -0,0 +1,267 @@
+use rayon::prelude::*;
+
+/// Which digits of the final signal to report.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Window {
+    /// `len` digits starting at `offset`.
+    At { offset: usize, len: usize },
+    /// `len` digits starting at the offset spelled out by the first `digits` digits of the
+    /// input signal, like Day 16's message offset.
+    FromPrefix { digits: usize, len: usize },
+}
+
+/// A configurable Flawed Frequency Transmission, running `process_from` (or `process_with`
+/// for other patterns, or the `process_second_half` shortcut) once per phase.
+///
+/// Output digit `p` of each phase multiplies the input by the base pattern with every value
+/// repeated `p + 1` times, skipping the very first value, and keeps the last digit of the sum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fft {
+    pattern: Vec<i64>,
+    phases: usize,
+    repeat: usize,
+    window: Window,
+}
+
+impl Default for Fft {
+    fn default() -> Self {
+        Fft {
+            pattern: vec![0, 1, 0, -1],
+            phases: 100,
+            repeat: 1,
+            window: Window::At { offset: 0, len: 8 },
+        }
+    }
+}
+
+impl Fft {
+    /// The puzzle's transmission: pattern `0, 1, 0, -1`, 100 phases and the first 8 digits.
+    pub fn new() -> Self {
+        Fft::default()
+    }
+
+    pub fn with_pattern(mut self, pattern: &[i64]) -> Self {
+        assert!(!pattern.is_empty(), "The base pattern can't be empty");
+        self.pattern = pattern.to_vec();
+        self
+    }
+
+    pub fn with_phases(mut self, phases: usize) -> Self {
+        self.phases = phases;
+        self
+    }
+
+    /// Treats the input as repeated `repeat` times end to end.
+    pub fn with_repeat(mut self, repeat: usize) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn with_window(mut self, window: Window) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// The digits in the window after every phase has run.
+    pub fn run(&self, input: &[u8]) -> Vec<u8> {
+        self.phases(input)
+            .last()
+            .unwrap_or_else(|| self.initial(input).window())
+    }
+
+    /// The digits in the window after each phase in turn.
+    pub fn phases(&self, input: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let mut state = self.initial(input);
+        (0..self.phases).map(move |_| {
+            state.step(&self.pattern);
+            state.window()
+        })
+    }
+
+    fn initial(&self, input: &[u8]) -> State {
+        let total = input.len() * self.repeat;
+        let (offset, len) = match self.window {
+            Window::At { offset, len } => (offset, len),
+            Window::FromPrefix { digits, len } => (
+                input[..digits]
+                    .iter()
+                    .fold(0, |acc, &d| acc * 10 + d as usize),
+                len,
+            ),
+        };
+        assert!(
+            offset + len <= total,
+            "The window is past the end of the signal"
+        );
+
+        // With a leading zero in the pattern, output digit `p` ignores every input digit before
+        // `p`, so only the digits from the window onwards need to be kept.
+        let base = if self.pattern[0] == 0 { offset } else { 0 };
+        State {
+            digits: (base..total).map(|i| input[i % input.len()]).collect(),
+            base,
+            window: (offset - base)..(offset - base + len),
+        }
+    }
+}
+
+struct State {
+    /// The signal from position `base` to the end.
+    digits: Vec<u8>,
+    base: usize,
+    window: std::ops::Range<usize>,
+}
+
+impl State {
+    fn window(&self) -> Vec<u8> {
+        self.digits[self.window.clone()].to_vec()
+    }
+
+    /// Runs one phase, using the suffix-sum shortcut when the whole tail is in the second half
+    /// of the signal.
+    fn step(&mut self, pattern: &[i64]) {
+        let end = self.base + self.digits.len();
+        if pattern.starts_with(&[0, 1]) && self.base * 2 >= end {
+            process_second_half(&mut self.digits);
+        } else if pattern == PATTERN {
+            process_from(&mut self.digits, self.base);
+        } else {
+            process_with(&mut self.digits, self.base, pattern);
+        }
+    }
+}
+
+/// The base pattern of the puzzle's transmission.
+pub(crate) const PATTERN: [i64; 4] = [0, 1, 0, -1];
+
+/// Runs one phase of the puzzle's transmission over a whole signal.
+#[cfg(test)]
+fn process(input: &mut [u8]) {
+    process_from(input, 0);
+}
+
+/// Runs one phase over the tail of a signal, where `input[0]` is the digit at `offset`.
+///
+/// Output digit `p` only depends on input digits from `p` onwards, so the tail can be
+/// processed without the digits before it. The pattern for digit `p` is made of runs of
+/// `p + 1` equal values, so each output digit is a handful of range sums.
+pub(crate) fn process_from(input: &mut [u8], offset: usize) {
+    process_with(input, offset, &PATTERN);
+}
+
+/// One phase over a tail that starts in the second half of the signal. There every pattern
+/// is zeros followed by ones running to the end, so each digit is a suffix sum.
+pub(crate) fn process_second_half(input: &mut [u8]) {
+    let mut acc = 0;
+    for digit in input.iter_mut().rev() {
+        acc = (acc + *digit) % 10;
+        *digit = acc;
+    }
+}
+
+/// `process_from` with any base pattern. A tail only makes sense if the pattern starts with
+/// zero; otherwise `offset` must be 0.
+///
+/// With prefix sums each run of equal pattern values takes one subtraction, and the digits
+/// are computed in parallel.
+pub(crate) fn process_with(input: &mut [u8], offset: usize, pattern: &[i64]) {
+    let mut prefix = vec![0i64; input.len() + 1];
+    for (idx, &d) in input.iter().enumerate() {
+        prefix[idx + 1] = prefix[idx] + d as i64;
+    }
+    let end = offset + input.len();
+
+    let output: Vec<u8> = (0..input.len())
+        .into_par_iter()
+        .map(|idx| {
+            let run = offset + idx + 1;
+            let mut sum = 0;
+            // Run `r` covers positions `r * run - 1` up to `(r + 1) * run - 1`; the first
+            // run is one shorter because the pattern's first value is skipped.
+            let mut r = 0;
+            while r * run < end + 1 {
+                let value = pattern[r % pattern.len()];
+                if value != 0 {
+                    let from = std::cmp::max(r * run, 1) - 1;
+                    let to = std::cmp::min((r + 1) * run - 1, end);
+                    sum += value * (prefix[to - offset] - prefix[from - offset]);
+                }
+                r += 1;
+            }
+            (sum % 10).unsigned_abs() as u8
+        })
+        .collect();
+    input.copy_from_slice(&output);
+}
+
+#[test]
+fn test_fft_phases() {
+    let fft = Fft::new()
+        .with_phases(4)
+        .with_window(Window::At { offset: 0, len: 8 });
+    let phases: Vec<Vec<u8>> = fft.phases(&[1, 2, 3, 4, 5, 6, 7, 8]).collect();
+    assert_eq!(
+        phases,
+        vec![
+            vec![4, 8, 2, 2, 6, 1, 5, 8],
+            vec![3, 4, 0, 4, 0, 4, 3, 8],
+            vec![0, 3, 4, 1, 5, 5, 1, 8],
+            vec![0, 1, 0, 2, 9, 4, 9, 8],
+        ]
+    );
+
+    // Only keeping the tail gives the same digits.
+    let tail = fft.clone().with_window(Window::At { offset: 3, len: 5 });
+    assert_eq!(tail.run(&[1, 2, 3, 4, 5, 6, 7, 8]), vec![2, 9, 4, 9, 8]);
+    // A window in the second half takes the suffix-sum shortcut.
+    let second_half = fft.clone().with_window(Window::At { offset: 4, len: 4 });
+    assert_eq!(second_half.run(&[1, 2, 3, 4, 5, 6, 7, 8]), vec![9, 4, 9, 8]);
+    let untouched = fft
+        .with_phases(0)
+        .with_window(Window::At { offset: 0, len: 3 });
+    assert_eq!(untouched.run(&[1, 2, 3]), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_fft_custom() {
+    // A pattern of `0, 1` sums every other run of digits.
+    let alternate = Fft::new()
+        .with_pattern(&[0, 1])
+        .with_phases(1)
+        .with_window(Window::At { offset: 0, len: 4 });
+    assert_eq!(alternate.run(&[1, 2, 3, 4]), vec![4, 5, 7, 4]);
+
+    let repeated = alternate
+        .clone()
+        .with_repeat(2)
+        .with_window(Window::At { offset: 0, len: 8 });
+    assert_eq!(repeated.run(&[1, 2, 3, 4]), vec![8, 0, 8, 0, 0, 9, 7, 4]);
+
+    let from_prefix = Fft::new()
+        .with_phases(1)
+        .with_window(Window::FromPrefix { digits: 1, len: 2 });
+    assert_eq!(from_prefix.run(&[3, 2, 3, 4, 5, 6, 7, 8]), vec![2, 6]);
+}
+
+#[test]
+fn test_day16_process_1() {
+    let mut input: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+    process(&mut input);
+    assert_eq!(input, [4, 8, 2, 2, 6, 1, 5, 8]);
+    process(&mut input);
+    assert_eq!(input, [3, 4, 0, 4, 0, 4, 3, 8]);
+}
+
+#[test]
+fn test_day16_process_from() {
+    let mut full: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+    let mut tail: [u8; 5] = [4, 5, 6, 7, 8];
+    let mut half: [u8; 4] = [5, 6, 7, 8];
+    for _ in 0..4 {
+        process(&mut full);
+        process_from(&mut tail, 3);
+        process_second_half(&mut half);
+        assert_eq!(full[3..], tail);
+        assert_eq!(full[4..], half);
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::helper::{orthogonal, Point};
+
+/// The state of a flood after one more minute.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FloodStep {
+    pub minute: usize,
+    /// The cells reached this minute.
+    pub front: Vec<Point>,
+    /// Every cell reached so far.
+    pub filled: HashSet<Point>,
+}
+
+/// Something spreading out from several sources at once, reaching every passable orthogonal
+/// neighbour of the filled cells each minute.
+///
+/// The first step is minute 0, with just the sources filled. The flood ends once a minute
+/// passes without reaching anything new.
+pub struct Flood<F> {
+    passable: F,
+    minute: usize,
+    front: Vec<Point>,
+    filled: HashSet<Point>,
+    started: bool,
+}
+
+impl<F: FnMut(Point) -> bool> Flood<F> {
+    pub fn new<I: IntoIterator<Item = Point>>(sources: I, passable: F) -> Self {
+        let filled: HashSet<Point> = sources.into_iter().collect();
+        let mut front: Vec<Point> = filled.iter().copied().collect();
+        front.sort_by_key(|p| (p.y(), p.x()));
+        Flood {
+            passable,
+            minute: 0,
+            front,
+            filled,
+            started: false,
+        }
+    }
+
+    /// How many minutes the flood takes to fill everything it can reach.
+    pub fn duration(self) -> usize {
+        self.last().map_or(0, |step| step.minute)
+    }
+}
+
+impl<F: FnMut(Point) -> bool> Iterator for Flood<F> {
+    type Item = FloodStep;
+
+    fn next(&mut self) -> Option<FloodStep> {
+        if self.started {
+            let mut front = vec![];
+            for &p in self.front.iter() {
+                for n in orthogonal(p) {
+                    if !self.filled.contains(&n) && (self.passable)(n) {
+                        self.filled.insert(n);
+                        front.push(n);
+                    }
+                }
+            }
+            front.sort_by_key(|p| (p.y(), p.x()));
+            self.front = front;
+            self.minute += 1;
+        }
+        self.started = true;
+
+        if self.front.is_empty() {
+            return None;
+        }
+        Some(FloodStep {
+            minute: self.minute,
+            front: self.front.clone(),
+            filled: self.filled.clone(),
+        })
+    }
+}
+
+#[test]
+fn test_flood() {
+    // A corridor along y = 0 from x = 0 to 6, flooded from both ends.
+    let corridor = |p: Point| p.y() == 0 && (0..=6).contains(&p.x());
+    let steps: Vec<FloodStep> =
+        Flood::new(vec![Point::new(0, 0), Point::new(6, 0)], corridor).collect();
+    assert_eq!(steps.len(), 4);
+    assert_eq!(steps[0].filled.len(), 2);
+    assert_eq!(steps[1].front, vec![Point::new(1, 0), Point::new(5, 0)]);
+    assert_eq!(steps[3].front, vec![Point::new(3, 0)]);
+    assert_eq!(steps[3].filled.len(), 7);
+
+    assert_eq!(Flood::new(vec![Point::new(0, 0)], corridor).duration(), 6);
+    assert_eq!(Flood::new(vec![], corridor).duration(), 0);
+}
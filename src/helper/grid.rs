@@ -0,0 +1,447 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use crate::helper::{Point, Rect};
+
+pub const NEIGHBOURS_4: [Point; 4] = [
+    Point::new(0, -1),
+    Point::new(1, 0),
+    Point::new(0, 1),
+    Point::new(-1, 0),
+];
+
+pub const NEIGHBOURS_8: [Point; 8] = [
+    Point::new(0, -1),
+    Point::new(1, -1),
+    Point::new(1, 0),
+    Point::new(1, 1),
+    Point::new(0, 1),
+    Point::new(-1, 1),
+    Point::new(-1, 0),
+    Point::new(-1, -1),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Storage<T> {
+    /// Row-major cells covering `width * height` points starting at `origin`
+    Dense {
+        origin: Point,
+        width: usize,
+        height: usize,
+        cells: Vec<T>,
+    },
+    /// Only the cells that have been set; everything else reads as `default`
+    Sparse {
+        cells: HashMap<Point, T>,
+        default: T,
+    },
+}
+
+/// A 2D grid of tiles indexed by `Point`, with `y` increasing downwards.
+///
+/// Dense grids have fixed bounds and panic when indexed outside them. Sparse grids grow as
+/// cells are written and read unset cells as their default tile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    storage: Storage<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid::from_vec(width, vec![fill; width * height])
+    }
+
+    pub fn sparse(default: T) -> Self {
+        Grid {
+            storage: Storage::Sparse {
+                cells: HashMap::default(),
+                default,
+            },
+        }
+    }
+
+    /// Builds a dense grid from row-major cells, like the fixed-size image buffers of Day 8.
+    pub fn from_slice(width: usize, cells: &[T]) -> Self {
+        Grid::from_vec(width, cells.to_vec())
+    }
+
+    /// Converts a sparse grid into a dense one covering its bounds. Dense grids are cloned.
+    pub fn to_dense(&self) -> Self {
+        match &self.storage {
+            Storage::Dense { .. } => self.clone(),
+            Storage::Sparse { default, .. } => {
+                let bounds = match self.bounds() {
+                    Some(b) => b,
+                    None => return Grid::from_vec(0, vec![]),
+                };
+                let mut res = Grid::new(bounds.width(), bounds.height(), default.clone())
+                    .with_origin(bounds.min);
+                for (p, t) in self.iter() {
+                    res[p] = t.clone();
+                }
+                res
+            }
+        }
+    }
+
+    /// Sets the cell at `p`. Panics if `p` is outside a dense grid.
+    pub fn set(&mut self, p: Point, val: T) {
+        self[p] = val;
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a dense grid from rows of cells, which must all be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, RaggedRowsError> {
+        let width = rows.first().map_or(0, |r| r.len());
+        if let Some((row, r)) = rows.iter().enumerate().find(|(_, r)| r.len() != width) {
+            return Err(RaggedRowsError {
+                row,
+                expected: width,
+                found: r.len(),
+            });
+        }
+        Ok(Grid::from_vec(width, rows.into_iter().flatten().collect()))
+    }
+
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Self {
+        let height = cells.len().checked_div(width).unwrap_or(0);
+        assert_eq!(width * height, cells.len(), "Cells do not fill the grid");
+        Grid {
+            storage: Storage::Dense {
+                origin: Point::new(0, 0),
+                width,
+                height,
+                cells,
+            },
+        }
+    }
+
+    /// Moves a dense grid so its top-left cell is at `origin`.
+    pub fn with_origin(mut self, new_origin: Point) -> Self {
+        if let Storage::Dense { origin, .. } = &mut self.storage {
+            *origin = new_origin;
+        }
+        self
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.storage, Storage::Sparse { .. })
+    }
+
+    fn offset(&self, p: Point) -> Option<usize> {
+        match &self.storage {
+            Storage::Dense {
+                origin,
+                width,
+                height,
+                ..
+            } => {
+                let d = p - *origin;
+                if d.x() < 0 || d.y() < 0 || d.x() >= *width as i64 || d.y() >= *height as i64 {
+                    None
+                } else {
+                    Some(d.y() as usize * width + d.x() as usize)
+                }
+            }
+            Storage::Sparse { .. } => None,
+        }
+    }
+
+    /// Returns the cell at `p`, or `None` if it is outside a dense grid or unset in a sparse one.
+    pub fn get(&self, p: Point) -> Option<&T> {
+        match &self.storage {
+            Storage::Dense { cells, .. } => self.offset(p).map(|idx| &cells[idx]),
+            Storage::Sparse { cells, .. } => cells.get(&p),
+        }
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        let offset = self.offset(p);
+        match &mut self.storage {
+            Storage::Dense { cells, .. } => offset.map(move |idx| &mut cells[idx]),
+            Storage::Sparse { cells, .. } => cells.get_mut(&p),
+        }
+    }
+
+    /// True if `p` is inside a dense grid, or has been set in a sparse one.
+    pub fn contains(&self, p: Point) -> bool {
+        self.get(p).is_some()
+    }
+
+    /// Number of cells stored.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Dense { cells, .. } => cells.len(),
+            Storage::Sparse { cells, .. } => cells.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The smallest rectangle containing every cell stored.
+    pub fn bounds(&self) -> Option<Rect> {
+        match &self.storage {
+            Storage::Dense {
+                origin,
+                width,
+                height,
+                ..
+            } => {
+                if *width == 0 || *height == 0 {
+                    None
+                } else {
+                    Some(Rect::new(
+                        *origin,
+                        *origin + Point::new(*width as i64 - 1, *height as i64 - 1),
+                    ))
+                }
+            }
+            Storage::Sparse { cells, .. } => Rect::from_points(cells.keys().copied()),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.bounds().map_or(0, |b| b.width())
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds().map_or(0, |b| b.height())
+    }
+
+    /// Iterates over stored cells. Dense grids are visited in row-major order.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (Point, &T)> + '_> {
+        match &self.storage {
+            Storage::Dense {
+                origin,
+                width,
+                cells,
+                ..
+            } => {
+                let (origin, width) = (*origin, *width);
+                Box::new(cells.iter().enumerate().map(move |(idx, t)| {
+                    let p = Point::new((idx % width) as i64, (idx / width) as i64);
+                    (origin + p, t)
+                }))
+            }
+            Storage::Sparse { cells, .. } => Box::new(cells.iter().map(|(&p, t)| (p, t))),
+        }
+    }
+
+    /// Points of the stored cells holding `val`.
+    pub fn find<'a>(&'a self, val: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter().filter(move |(_, t)| *t == val).map(|(p, _)| p)
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        p: Point,
+        deltas: &'static [Point],
+    ) -> impl Iterator<Item = (Point, &'a T)> + 'a {
+        deltas
+            .iter()
+            .filter_map(move |&d| self.get(p + d).map(|t| (p + d, t)))
+    }
+
+    /// The stored orthogonal neighbours of `p`, clockwise from north.
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours(p, &NEIGHBOURS_4)
+    }
+
+    /// The stored orthogonal and diagonal neighbours of `p`, clockwise from north.
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours(p, &NEIGHBOURS_8)
+    }
+
+    /// Iterates over row `y` within the grid's bounds, reading unset sparse cells as the default.
+    pub fn row(&self, y: i64) -> impl Iterator<Item = &T> {
+        let (min, max) = self
+            .bounds()
+            .map_or((Point::new(0, 0), Point::new(-1, -1)), |b| (b.min, b.max));
+        (min.x()..=max.x()).map(move |x| &self[Point::new(x, y)])
+    }
+
+    /// Iterates over column `x` within the grid's bounds, reading unset sparse cells as the
+    /// default.
+    pub fn column(&self, x: i64) -> impl Iterator<Item = &T> {
+        let (min, max) = self
+            .bounds()
+            .map_or((Point::new(0, 0), Point::new(-1, -1)), |b| (b.min, b.max));
+        (min.y()..=max.y()).map(move |y| &self[Point::new(x, y)])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        let (min, max) = self
+            .bounds()
+            .map_or((Point::new(0, 0), Point::new(-1, -1)), |b| (b.min, b.max));
+        (min.y()..=max.y()).map(move |y| self.row(y))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        let (min, max) = self
+            .bounds()
+            .map_or((Point::new(0, 0), Point::new(-1, -1)), |b| (b.min, b.max));
+        (min.x()..=max.x()).map(move |x| self.column(x))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, mut f: F) -> Grid<U> {
+        Grid {
+            storage: match &self.storage {
+                Storage::Dense {
+                    origin,
+                    width,
+                    height,
+                    cells,
+                } => Storage::Dense {
+                    origin: *origin,
+                    width: *width,
+                    height: *height,
+                    cells: cells.iter().map(f).collect(),
+                },
+                Storage::Sparse { cells, default } => Storage::Sparse {
+                    default: f(default),
+                    cells: cells.iter().map(|(&p, t)| (p, f(t))).collect(),
+                },
+            },
+        }
+    }
+}
+
+impl<T> std::ops::Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &Self::Output {
+        match &self.storage {
+            Storage::Dense { .. } => self
+                .get(p)
+                .unwrap_or_else(|| panic!("{:?} is outside the grid", p)),
+            Storage::Sparse { cells, default } => cells.get(&p).unwrap_or(default),
+        }
+    }
+}
+
+impl<T: Clone> std::ops::IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut Self::Output {
+        let offset = self.offset(p);
+        match &mut self.storage {
+            Storage::Dense { cells, .. } => {
+                &mut cells[offset.unwrap_or_else(|| panic!("{:?} is outside the grid", p))]
+            }
+            Storage::Sparse { cells, default } => cells.entry(p).or_insert_with(|| default.clone()),
+        }
+    }
+}
+
+/// Rows of cells, like the map built by Day 17.
+impl<T> TryFrom<Vec<Vec<T>>> for Grid<T> {
+    type Error = RaggedRowsError;
+
+    fn try_from(rows: Vec<Vec<T>>) -> Result<Self, Self::Error> {
+        Grid::from_rows(rows)
+    }
+}
+
+/// Row `row` (counting from 0) is `found` cells wide, but the first row is `expected` wide.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RaggedRowsError {
+    pub row: usize,
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl std::fmt::Display for RaggedRowsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "row {} is {} cells wide, expected {}",
+            self.row, self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for RaggedRowsError {}
+
+impl<T: Default> From<HashMap<Point, T>> for Grid<T> {
+    fn from(cells: HashMap<Point, T>) -> Self {
+        Grid {
+            storage: Storage::Sparse {
+                cells,
+                default: T::default(),
+            },
+        }
+    }
+}
+
+impl<T: Default> From<HashMap<(i64, i64), T>> for Grid<T> {
+    fn from(cells: HashMap<(i64, i64), T>) -> Self {
+        cells
+            .into_iter()
+            .map(|(p, t)| (Point::from(p), t))
+            .collect::<HashMap<_, _>>()
+            .into()
+    }
+}
+
+#[test]
+fn test_grid_dense() {
+    let grid = Grid::from_rows(vec![vec![1u8, 2, 3], vec![4, 5, 6]]).unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[Point::new(1, 1)], 5);
+    assert_eq!(grid.get(Point::new(3, 0)), None);
+    assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), vec![4, 5, 6]);
+    assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), vec![3, 6]);
+
+    let n4 = grid.neighbours4(Point::new(0, 0)).map(|(_, &t)| t);
+    assert_eq!(n4.collect::<Vec<_>>(), vec![2, 4]);
+    assert_eq!(grid.neighbours8(Point::new(1, 0)).count(), 5);
+
+    let grid = grid.with_origin(Point::new(-1, -1));
+    assert_eq!(grid[Point::new(0, 0)], 5);
+    assert_eq!(
+        grid.bounds(),
+        Some(Rect::new(Point::new(-1, -1), Point::new(1, 0)))
+    );
+}
+
+#[test]
+fn test_grid_sparse() {
+    let mut painting: HashMap<(i64, i64), bool> = HashMap::new();
+    painting.insert((-2, 1), true);
+    painting.insert((3, -1), false);
+    let mut grid: Grid<bool> = painting.into();
+    assert!(grid.is_sparse());
+    assert_eq!(
+        grid.bounds(),
+        Some(Rect::new(Point::new(-2, -1), Point::new(3, 1)))
+    );
+    assert!(!grid[Point::new(100, 100)]);
+
+    grid[Point::new(0, 0)] = true;
+    assert_eq!(grid.len(), 3);
+    assert_eq!(grid.find(&true).count(), 2);
+
+    let dense = grid.to_dense();
+    assert_eq!((dense.width(), dense.height()), (6, 3));
+    assert!(dense[Point::new(-2, 1)]);
+    assert_eq!(dense.rows().count(), 3);
+}
+
+#[test]
+fn test_grid_ragged_rows() {
+    // Six cells would fill a 3x2 grid, but the rows don't line up.
+    let rows = vec![vec![1, 2, 3], vec![4], vec![5, 6]];
+    assert_eq!(
+        Grid::try_from(rows),
+        Err(RaggedRowsError {
+            row: 1,
+            expected: 3,
+            found: 1
+        })
+    );
+    assert_eq!(Grid::<u8>::from_rows(vec![]).map(|g| g.width()), Ok(0));
+}
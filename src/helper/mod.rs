@@ -1,29 +1,29 @@
+mod ascii_map;
+mod asteroid_field;
+mod bounds;
+mod explorer;
+mod fft;
+mod flood;
+mod grid;
+mod ocr;
 mod point;
-mod point3;
+mod render;
+mod routine;
+mod search;
+mod segment;
+mod vector;
 
+pub use ascii_map::*;
+pub use asteroid_field::*;
+pub use bounds::*;
+pub use explorer::*;
+pub use fft::*;
+pub use flood::*;
+pub use grid::*;
+pub use ocr::*;
 pub use point::*;
-pub use point3::*;
-
-pub fn replace_with<T>(list: &mut Vec<T>, replace: &[T], with: &[T]) -> bool
-where
-    T: PartialEq<T> + Clone,
-{
-    let first = list
-        .as_slice()
-        .windows(replace.len())
-        .enumerate()
-        .find(|&(_, s)| s == replace)
-        .map(|(idx, _)| idx);
-    if let Some(idx) = first {
-        list.splice(idx..(idx + replace.len()), with.iter().cloned());
-        return true;
-    }
-    false
-}
-
-pub fn replace_all_with<T>(list: &mut Vec<T>, replace: &[T], with: &[T])
-where
-    T: PartialEq<T> + Clone,
-{
-    while replace_with(list, replace, with) {}
-}
+pub use render::*;
+pub use routine::*;
+pub use search::*;
+pub use segment::*;
+pub use vector::*;
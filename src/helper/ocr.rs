@@ -0,0 +1,254 @@
+use crate::helper::{Grid, Point, Rect};
+
+/// The 6 pixel high capitals, one glyph per entry with rows separated by `\n`.
+const SMALL_FONT: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+/// The 10 pixel high capitals.
+const LARGE_FONT: &[(char, &str)] = &[
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OcrError {
+    /// Only 6 and 10 pixel high text can be read.
+    UnsupportedHeight(usize),
+    /// The ink starting at column `column` doesn't match any glyph. `glyph` is what was found
+    /// there, drawn with `#` and `.`.
+    UnknownGlyph { column: usize, glyph: String },
+}
+
+impl std::fmt::Display for OcrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OcrError::UnsupportedHeight(h) => write!(f, "no font is {} pixels high", h),
+            OcrError::UnknownGlyph { column, glyph } => {
+                write!(f, "unknown glyph at column {}:\n{}", column, glyph)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Reads the text drawn in `grid`, treating cells for which `is_ink` holds as lit. Blank
+/// rows and columns around the text are ignored.
+pub fn ocr_grid<T, F: Fn(&T) -> bool>(grid: &Grid<T>, is_ink: F) -> Result<String, OcrError> {
+    let ink: Vec<Point> = grid
+        .iter()
+        .filter(|(_, t)| is_ink(t))
+        .map(|(p, _)| p)
+        .collect();
+    let bounds = match Rect::from_points(ink.iter().copied()) {
+        Some(b) => b,
+        None => return Ok(String::new()),
+    };
+    let (width, height) = (bounds.width(), bounds.height());
+    let mut pixels = vec![false; width * height];
+    for p in ink {
+        let d = p - bounds.min;
+        pixels[d.y() as usize * width + d.x() as usize] = true;
+    }
+    read_pixels(&pixels, width, height)
+}
+
+/// Reads the text in a row-major image buffer `width` pixels wide, where pixels equal to `ink`
+/// are lit.
+pub fn ocr_buffer(buffer: &[u8], width: usize, ink: u8) -> Result<String, OcrError> {
+    ocr_grid(&Grid::from_slice(width, buffer), |&p| p == ink)
+}
+
+/// Matches glyphs left to right. Letters are usually separated by a blank column, but a
+/// few (like the small 'Y') fill their whole cell, so glyphs are matched on their own
+/// width rather than split on gaps.
+fn read_pixels(pixels: &[bool], width: usize, height: usize) -> Result<String, OcrError> {
+    let font = match height {
+        6 => SMALL_FONT,
+        10 => LARGE_FONT,
+        h => return Err(OcrError::UnsupportedHeight(h)),
+    };
+    let column_blank = |x: usize| (0..height).all(|y| !pixels[y * width + x]);
+    let matches = |glyph: &str, x: usize| {
+        glyph.lines().enumerate().all(|(y, row)| {
+            x + row.len() <= width
+                && row
+                    .chars()
+                    .enumerate()
+                    .all(|(dx, c)| (c == '#') == pixels[y * width + x + dx])
+        })
+    };
+
+    let mut res = String::new();
+    let mut x = 0;
+    while x < width {
+        if column_blank(x) {
+            x += 1;
+            continue;
+        }
+        let found = font
+            .iter()
+            .filter(|(_, glyph)| matches(glyph, x))
+            .max_by_key(|(_, glyph)| glyph.find('\n'));
+        match found {
+            Some((ch, glyph)) => {
+                res.push(*ch);
+                x += glyph.find('\n').unwrap_or(0);
+            }
+            None => {
+                let end = (x..width).find(|&x| column_blank(x)).unwrap_or(width);
+                let glyph = (0..height)
+                    .map(|y| {
+                        (x..end)
+                            .map(|x| if pixels[y * width + x] { '#' } else { '.' })
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                return Err(OcrError::UnknownGlyph { column: x, glyph });
+            }
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+fn draw(font: &[(char, &str)], text: &str, gap: usize) -> Grid<bool> {
+    let glyphs: Vec<&str> = text
+        .chars()
+        .map(|c| font.iter().find(|(ch, _)| *ch == c).unwrap().1)
+        .collect();
+    let height = glyphs[0].lines().count();
+    let rows: Vec<Vec<bool>> = (0..height)
+        .map(|y| {
+            let mut row = vec![false];
+            for glyph in &glyphs {
+                row.extend(glyph.lines().nth(y).unwrap().chars().map(|c| c == '#'));
+                row.extend(std::iter::repeat_n(false, gap));
+            }
+            row
+        })
+        .collect();
+    Grid::from_rows(rows).unwrap()
+}
+
+#[test]
+fn test_ocr_small() {
+    assert_eq!(
+        ocr_grid(&draw(SMALL_FONT, "HYPERBOLIZ", 1), |&b| b).unwrap(),
+        "HYPERBOLIZ"
+    );
+    let alphabet: String = SMALL_FONT.iter().map(|(c, _)| c).collect();
+    assert_eq!(
+        ocr_grid(&draw(SMALL_FONT, &alphabet, 1), |&b| b).unwrap(),
+        alphabet
+    );
+
+    let buffer: Vec<u8> = "0110\n1001\n1001\n1111\n1001\n1001"
+        .chars()
+        .filter_map(|c| c.to_digit(10).map(|d| d as u8))
+        .collect();
+    assert_eq!(ocr_buffer(&buffer, 4, 1).unwrap(), "A");
+}
+
+#[test]
+fn test_ocr_large() {
+    let alphabet: String = LARGE_FONT.iter().map(|(c, _)| c).collect();
+    assert_eq!(
+        ocr_grid(&draw(LARGE_FONT, &alphabet, 2), |&b| b).unwrap(),
+        alphabet
+    );
+}
+
+#[test]
+fn test_ocr_errors() {
+    let mut grid = draw(SMALL_FONT, "AB", 1);
+    grid.set(Point::new(9, 0), true);
+    assert_eq!(
+        ocr_grid(&grid, |&b| b).unwrap_err(),
+        OcrError::UnknownGlyph {
+            column: 5,
+            glyph: "####\n#..#\n###.\n#..#\n#..#\n###.".to_string()
+        }
+    );
+    assert_eq!(
+        ocr_buffer(&[1, 1, 1], 3, 1).unwrap_err(),
+        OcrError::UnsupportedHeight(1)
+    );
+}
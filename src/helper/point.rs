@@ -1,109 +1,160 @@
+use crate::helper::Point;
+
+/// A compass heading on a grid where `y` increases downwards, so north is `(0, -1)`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Point {
-    pub x: i64,
-    pub y: i64,
+pub enum Heading {
+    North,
+    East,
+    South,
+    West,
 }
 
-impl Point {
-    pub fn new(x: i64, y: i64) -> Self {
-        Point { x, y }
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Right,
+    Around,
+}
+
+impl Heading {
+    /// Every heading, clockwise from north.
+    pub const ALL: [Heading; 4] = [Heading::North, Heading::East, Heading::South, Heading::West];
+
+    pub fn compass() -> impl Iterator<Item = Heading> {
+        Heading::ALL.iter().copied()
     }
-    pub fn manhattan(&self) -> i64 {
-        self.x.abs() + self.y.abs()
+
+    fn from_index(idx: usize) -> Heading {
+        Heading::ALL[idx % 4]
     }
-}
 
-impl std::ops::Add<Point> for Point {
-    type Output = Point;
+    fn index(self) -> usize {
+        self as usize
+    }
 
-    fn add(self, rhs: Point) -> Self::Output {
-        Point {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
+    pub fn turn(self, turn: Turn) -> Heading {
+        match turn {
+            Turn::Left => self.left(),
+            Turn::Right => self.right(),
+            Turn::Around => self.around(),
         }
     }
-}
-impl std::ops::AddAssign for Point {
-    fn add_assign(&mut self, rhs: Point) {
-        self.x += rhs.x;
-        self.y += rhs.y;
+
+    pub fn left(self) -> Heading {
+        Heading::from_index(self.index() + 3)
     }
-}
-impl std::ops::Sub<Point> for Point {
-    type Output = Point;
 
-    fn sub(self, rhs: Point) -> Self::Output {
-        Point {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
-        }
+    pub fn right(self) -> Heading {
+        Heading::from_index(self.index() + 1)
     }
-}
-impl std::ops::SubAssign for Point {
-    fn sub_assign(&mut self, rhs: Point) {
-        self.x -= rhs.x;
-        self.y -= rhs.y;
+
+    pub fn around(self) -> Heading {
+        Heading::from_index(self.index() + 2)
     }
-}
-impl std::ops::Mul<i64> for Point {
-    type Output = Point;
 
-    fn mul(self, rhs: i64) -> Self::Output {
-        Point {
-            x: self.x * rhs,
-            y: self.y * rhs,
+    /// The turn that faces `self` towards `other`, or `None` if it already does.
+    pub fn turn_to(self, other: Heading) -> Option<Turn> {
+        match (other.index() + 4 - self.index()) % 4 {
+            1 => Some(Turn::Right),
+            2 => Some(Turn::Around),
+            3 => Some(Turn::Left),
+            _ => None,
         }
     }
-}
-impl std::ops::Mul<Point> for i64 {
-    type Output = Point;
 
-    fn mul(self, rhs: Point) -> Self::Output {
-        Point {
-            x: self * rhs.x,
-            y: self * rhs.y,
+    /// The unit step in this direction.
+    pub fn delta(self) -> Point {
+        match self {
+            Heading::North => Point::new(0, -1),
+            Heading::East => Point::new(1, 0),
+            Heading::South => Point::new(0, 1),
+            Heading::West => Point::new(-1, 0),
         }
     }
-}
-impl std::ops::MulAssign<i64> for Point {
-    fn mul_assign(&mut self, rhs: i64) {
-        self.x *= rhs;
-        self.y *= rhs;
+
+    /// The heading of a unit step, if `delta` is one.
+    pub fn from_delta(delta: Point) -> Option<Heading> {
+        Heading::compass().find(|h| h.delta() == delta)
+    }
+
+    /// Reads `U`/`D`/`L`/`R`, `^`/`v`/`<`/`>` or `N`/`S`/`E`/`W`.
+    pub fn from_char(ch: char) -> Option<Heading> {
+        match ch {
+            'U' | '^' | 'N' => Some(Heading::North),
+            'R' | '>' | 'E' => Some(Heading::East),
+            'D' | 'v' | 'S' => Some(Heading::South),
+            'L' | '<' | 'W' => Some(Heading::West),
+            _ => None,
+        }
     }
-}
-impl std::ops::Div<i64> for Point {
-    type Output = Point;
 
-    fn div(self, rhs: i64) -> Self::Output {
-        Point {
-            x: self.x / rhs,
-            y: self.y / rhs,
+    pub fn to_arrow(self) -> char {
+        match self {
+            Heading::North => '^',
+            Heading::East => '>',
+            Heading::South => 'v',
+            Heading::West => '<',
         }
     }
 }
-impl std::ops::DivAssign<i64> for Point {
-    fn div_assign(&mut self, rhs: i64) {
-        self.x /= rhs;
-        self.y /= rhs;
+
+impl Turn {
+    /// Reads `L` or `R`.
+    pub fn from_char(ch: char) -> Option<Turn> {
+        match ch {
+            'L' => Some(Turn::Left),
+            'R' => Some(Turn::Right),
+            _ => None,
+        }
     }
 }
-impl std::ops::Neg for Point {
+
+impl std::ops::Add<Heading> for Point {
     type Output = Point;
 
-    fn neg(self) -> Self::Output {
-        Point {
-            x: -self.x,
-            y: -self.y,
-        }
+    fn add(self, rhs: Heading) -> Self::Output {
+        self + rhs.delta()
     }
 }
-impl From<(i64, i64)> for Point {
-    fn from((x, y): (i64, i64)) -> Self {
-        Point { x, y }
+impl std::ops::AddAssign<Heading> for Point {
+    fn add_assign(&mut self, rhs: Heading) {
+        *self += rhs.delta();
     }
 }
-impl Into<(i64, i64)> for Point {
-    fn into(self) -> (i64, i64) {
-        (self.x, self.y)
+
+#[test]
+fn test_heading() {
+    assert_eq!(Heading::North.left(), Heading::West);
+    assert_eq!(Heading::West.right(), Heading::North);
+    assert_eq!(Heading::East.turn(Turn::Around), Heading::West);
+    assert_eq!(Heading::South.turn_to(Heading::East), Some(Turn::Left));
+    assert_eq!(Heading::South.turn_to(Heading::South), None);
+
+    for h in Heading::compass() {
+        assert_eq!(Heading::from_delta(h.delta()), Some(h));
+        assert_eq!(Heading::from_char(h.to_arrow()), Some(h));
+        assert_eq!(h.left().right(), h);
+        assert_eq!(Point::new(0, 0) + h + h.around(), Point::new(0, 0));
     }
+    assert_eq!(
+        "UDLRNSEW^v<>"
+            .chars()
+            .map(Heading::from_char)
+            .collect::<Option<Vec<_>>>(),
+        Some(vec![
+            Heading::North,
+            Heading::South,
+            Heading::West,
+            Heading::East,
+            Heading::North,
+            Heading::South,
+            Heading::East,
+            Heading::West,
+            Heading::North,
+            Heading::South,
+            Heading::West,
+            Heading::East,
+        ])
+    );
+    assert_eq!(Heading::from_char('x'), None);
 }
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::helper::{Grid, Point, Rect};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+    /// Perceived brightness, for greyscale output.
+    pub fn luma(self) -> u8 {
+        ((299 * self.0 as u32 + 587 * self.1 as u32 + 114 * self.2 as u32) / 1000) as u8
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub ch: char,
+    pub colour: Rgb,
+}
+
+/// How each kind of tile is drawn. Tiles without an entry use the default glyph.
+#[derive(Clone, Debug)]
+pub struct Palette<T: Eq + Hash> {
+    glyphs: HashMap<T, Glyph>,
+    default: Glyph,
+}
+
+impl<T: Eq + Hash> Palette<T> {
+    pub fn new(ch: char, colour: Rgb) -> Self {
+        Palette {
+            glyphs: HashMap::default(),
+            default: Glyph { ch, colour },
+        }
+    }
+
+    pub fn with(mut self, tile: T, ch: char, colour: Rgb) -> Self {
+        self.glyphs.insert(tile, Glyph { ch, colour });
+        self
+    }
+
+    pub fn glyph(&self, tile: &T) -> Glyph {
+        self.glyphs.get(tile).copied().unwrap_or(self.default)
+    }
+}
+
+/// Walks the grid's bounds row by row. Unset sparse cells are drawn as the grid's default.
+fn for_each_row<T, F: FnMut(Vec<&T>)>(grid: &Grid<T>, mut f: F) {
+    if let Some(Rect { min, max }) = grid.bounds() {
+        for y in min.y()..=max.y() {
+            f((min.x()..=max.x())
+                .map(|x| &grid[Point::new(x, y)])
+                .collect());
+        }
+    }
+}
+
+/// Draws the grid as text, one line per row. With `ansi`, each glyph is coloured with a 24-bit
+/// terminal escape code.
+pub fn render_text<T: Eq + Hash>(grid: &Grid<T>, palette: &Palette<T>, ansi: bool) -> String {
+    let mut res = String::new();
+    for_each_row(grid, |row| {
+        let mut current = None;
+        for tile in row {
+            let glyph = palette.glyph(tile);
+            if ansi && current != Some(glyph.colour) {
+                let Rgb(r, g, b) = glyph.colour;
+                res.push_str(&format!("\x1b[38;2;{};{};{}m", r, g, b));
+                current = Some(glyph.colour);
+            }
+            res.push(glyph.ch);
+        }
+        if ansi {
+            res.push_str("\x1b[0m");
+        }
+        res.push('\n');
+    });
+    res
+}
+
+fn write_image<T, W, F>(
+    grid: &Grid<T>,
+    scale: usize,
+    out: &mut W,
+    magic: &str,
+    mut pixel: F,
+) -> io::Result<()>
+where
+    W: Write,
+    F: FnMut(&T) -> String,
+{
+    let scale = std::cmp::max(scale, 1);
+    writeln!(out, "{}", magic)?;
+    writeln!(out, "{} {}", grid.width() * scale, grid.height() * scale)?;
+    writeln!(out, "255")?;
+    let mut res = Ok(());
+    for_each_row(grid, |row| {
+        let line = row
+            .iter()
+            .flat_map(|t| std::iter::repeat_n(pixel(t), scale))
+            .collect::<Vec<_>>()
+            .join(" ");
+        for _ in 0..scale {
+            if res.is_ok() {
+                res = writeln!(out, "{}", line);
+            }
+        }
+    });
+    res
+}
+
+/// Writes the grid as a plain-text (P3) PPM image, drawing each cell as a `scale`-pixel square.
+pub fn write_ppm<T: Eq + Hash, W: Write>(
+    grid: &Grid<T>,
+    palette: &Palette<T>,
+    scale: usize,
+    out: &mut W,
+) -> io::Result<()> {
+    write_image(grid, scale, out, "P3", |t| {
+        let Rgb(r, g, b) = palette.glyph(t).colour;
+        format!("{} {} {}", r, g, b)
+    })
+}
+
+/// Writes the grid as a plain-text (P2) PGM image, using the brightness of each palette colour.
+pub fn write_pgm<T: Eq + Hash, W: Write>(
+    grid: &Grid<T>,
+    palette: &Palette<T>,
+    scale: usize,
+    out: &mut W,
+) -> io::Result<()> {
+    write_image(grid, scale, out, "P2", |t| {
+        format!("{}", palette.glyph(t).colour.luma())
+    })
+}
+
+/// Saves the grid as an image, picking PGM or PPM from the file extension.
+pub fn save_image<T: Eq + Hash, P: AsRef<Path>>(
+    grid: &Grid<T>,
+    palette: &Palette<T>,
+    scale: usize,
+    path: P,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut out = io::BufWriter::new(std::fs::File::create(path)?);
+    if path.extension().is_some_and(|e| e == "pgm") {
+        write_pgm(grid, palette, scale, &mut out)
+    } else {
+        write_ppm(grid, palette, scale, &mut out)
+    }
+}
+
+#[test]
+fn test_render_text() {
+    let grid = Grid::from_slice(3, &[0u8, 1, 0, 1, 2, 1]);
+    let palette = Palette::new('?', Rgb::BLACK)
+        .with(0, ' ', Rgb::BLACK)
+        .with(1, '#', Rgb::WHITE);
+    assert_eq!(render_text(&grid, &palette, false), " # \n#?#\n");
+
+    let ansi = render_text(&grid, &palette, true);
+    assert!(ansi.starts_with("\x1b[38;2;0;0;0m \x1b[38;2;255;255;255m#"));
+    assert_eq!(ansi.matches("\x1b[0m\n").count(), 2);
+}
+
+#[test]
+fn test_write_images() {
+    let grid = Grid::from_slice(2, &[false, true]);
+    let palette = Palette::new(' ', Rgb::BLACK).with(true, '#', Rgb(255, 0, 0));
+
+    let mut ppm = vec![];
+    write_ppm(&grid, &palette, 1, &mut ppm).unwrap();
+    assert_eq!(
+        String::from_utf8(ppm).unwrap(),
+        "P3\n2 1\n255\n0 0 0 255 0 0\n"
+    );
+
+    let mut pgm = vec![];
+    write_pgm(&grid, &palette, 2, &mut pgm).unwrap();
+    assert_eq!(
+        String::from_utf8(pgm).unwrap(),
+        "P2\n4 2\n255\n0 0 76 76\n0 0 76 76\n"
+    );
+}
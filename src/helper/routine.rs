@@ -0,0 +1,376 @@
+use std::fmt;
+
+use itertools::Itertools;
+
+use crate::helper::Turn;
+
+/// One command for a vacuum robot: turn on the spot or move forward.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Turn(Turn),
+    Forward(usize),
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Turn(Turn::Left) => write!(f, "L"),
+            Move::Turn(Turn::Right) => write!(f, "R"),
+            Move::Turn(Turn::Around) => write!(f, "L,L"),
+            Move::Forward(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// The smallest pieces a path can be cut into: a single quarter turn or a single step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Step {
+    Turn(Turn),
+    Forward,
+}
+
+fn to_steps(moves: &[Move]) -> Vec<Step> {
+    let mut steps = vec![];
+    for &m in moves {
+        match m {
+            Move::Turn(Turn::Around) => steps.extend([Step::Turn(Turn::Left); 2]),
+            Move::Turn(t) => steps.push(Step::Turn(t)),
+            Move::Forward(n) => steps.extend(std::iter::repeat_n(Step::Forward, n)),
+        }
+    }
+    steps
+}
+
+/// Joins runs of single steps back into moves.
+fn to_moves(steps: &[Step]) -> Vec<Move> {
+    let mut moves = vec![];
+    for &s in steps {
+        match (s, moves.last_mut()) {
+            (Step::Forward, Some(Move::Forward(n))) => *n += 1,
+            (Step::Forward, _) => moves.push(Move::Forward(1)),
+            (Step::Turn(t), _) => moves.push(Move::Turn(t)),
+        }
+    }
+    moves
+}
+
+/// A path split into a main routine that only calls movement functions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Routines {
+    /// Indices into `functions`, in calling order.
+    pub main: Vec<usize>,
+    pub functions: Vec<Vec<Move>>,
+}
+
+impl Routines {
+    /// The main routine as the robot reads it, naming the functions `A`, `B`, `C`...
+    pub fn main_routine(&self) -> String {
+        self.main
+            .iter()
+            .map(|&f| (b'A' + f as u8) as char)
+            .join(",")
+    }
+
+    pub fn function(&self, idx: usize) -> String {
+        self.functions[idx].iter().join(",")
+    }
+
+    /// The path the routines walk, with consecutive forward moves joined.
+    pub fn expand(&self) -> Vec<Move> {
+        let steps: Vec<Step> = self
+            .main
+            .iter()
+            .flat_map(|&f| to_steps(&self.functions[f]))
+            .collect();
+        to_moves(&steps)
+    }
+}
+
+impl std::str::FromStr for Routines {
+    type Err = RoutineParseError;
+
+    /// Parses the robot's input: the main routine, then one line per function.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate();
+        let (_, main) = lines.next().unwrap_or((0, ""));
+        let main = main
+            .split(',')
+            .filter(|t| !t.is_empty())
+            .map(|t| match t.as_bytes() {
+                [f @ b'A'..=b'Z'] => Ok((f - b'A') as usize),
+                _ => Err(RoutineParseError {
+                    line: 1,
+                    token: t.to_string(),
+                }),
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        let functions = lines
+            .map(|(idx, line)| {
+                line.split(',')
+                    .filter(|t| !t.is_empty())
+                    .map(|t| match t {
+                        "L" => Ok(Move::Turn(Turn::Left)),
+                        "R" => Ok(Move::Turn(Turn::Right)),
+                        _ => t.parse().map(Move::Forward).map_err(|_| RoutineParseError {
+                            line: idx + 1,
+                            token: t.to_string(),
+                        }),
+                    })
+                    .collect::<Result<Vec<Move>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(&f) = main.iter().find(|&&f| f >= functions.len()) {
+            return Err(RoutineParseError {
+                line: 1,
+                token: ((b'A' + f as u8) as char).to_string(),
+            });
+        }
+        Ok(Routines { main, functions })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoutineParseError {
+    /// Lines start at 1, with the main routine on line 1.
+    pub line: usize,
+    pub token: String,
+}
+
+impl fmt::Display for RoutineParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid command {:?} on line {}", self.token, self.line)
+    }
+}
+
+impl std::error::Error for RoutineParseError {}
+
+/// No main routine and set of functions fit within the limits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CompressError {
+    pub functions: usize,
+    pub line_limit: usize,
+}
+
+impl fmt::Display for CompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the path can't be encoded with {} functions of at most {} characters per line",
+            self.functions, self.line_limit
+        )
+    }
+}
+
+impl std::error::Error for CompressError {}
+
+/// Searches for a main routine and movement functions that walk a path, keeping every line
+/// within a character limit.
+///
+/// Forward moves may be split between functions, so `R,10` can be walked as a function ending
+/// `R,4` followed by one starting `6`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Compressor {
+    functions: usize,
+    line_limit: usize,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Compressor {
+            functions: 3,
+            line_limit: 20,
+        }
+    }
+}
+
+impl Compressor {
+    /// The robot's limits: three functions and 20 characters per line.
+    pub fn new() -> Self {
+        Compressor::default()
+    }
+
+    pub fn with_functions(mut self, functions: usize) -> Self {
+        assert!(functions <= 26, "Functions are named with a single letter");
+        self.functions = functions;
+        self
+    }
+
+    /// The longest line allowed, not counting the newline.
+    pub fn with_line_limit(mut self, line_limit: usize) -> Self {
+        self.line_limit = line_limit;
+        self
+    }
+
+    pub fn compress(&self, path: &[Move]) -> Result<Routines, CompressError> {
+        let mut search = Search {
+            path: to_steps(path),
+            max_functions: self.functions,
+            line_limit: self.line_limit,
+            // One letter per call plus the commas between them.
+            max_calls: self.line_limit.div_ceil(2),
+            functions: vec![],
+            main: vec![],
+        };
+        if search.solve(0) {
+            Ok(Routines {
+                main: search.main,
+                functions: search.functions.iter().map(|f| to_moves(f)).collect(),
+            })
+        } else {
+            Err(CompressError {
+                functions: self.functions,
+                line_limit: self.line_limit,
+            })
+        }
+    }
+}
+
+struct Search {
+    path: Vec<Step>,
+    max_functions: usize,
+    line_limit: usize,
+    max_calls: usize,
+    functions: Vec<Vec<Step>>,
+    main: Vec<usize>,
+}
+
+impl Search {
+    /// Covers the path from `pos` onwards, first with the functions already defined and then by
+    /// defining a new one starting at `pos`.
+    fn solve(&mut self, pos: usize) -> bool {
+        if pos == self.path.len() {
+            return true;
+        }
+        if self.main.len() == self.max_calls {
+            return false;
+        }
+
+        for idx in 0..self.functions.len() {
+            let len = self.functions[idx].len();
+            if self.path[pos..].starts_with(&self.functions[idx]) {
+                self.main.push(idx);
+                if self.solve(pos + len) {
+                    return true;
+                }
+                self.main.pop();
+            }
+        }
+
+        if self.functions.len() < self.max_functions {
+            for end in self.candidate_ends(pos) {
+                self.functions.push(self.path[pos..end].to_vec());
+                self.main.push(self.functions.len() - 1);
+                if self.solve(end) {
+                    return true;
+                }
+                self.main.pop();
+                self.functions.pop();
+            }
+        }
+        false
+    }
+
+    /// Where a new function starting at `pos` could end, longest first. Ends between whole
+    /// moves are tried before those that split a forward move.
+    fn candidate_ends(&self, pos: usize) -> Vec<usize> {
+        let fits: Vec<usize> = (pos + 1..=self.path.len())
+            .take_while(|&end| {
+                to_moves(&self.path[pos..end]).iter().join(",").len() <= self.line_limit
+            })
+            .collect();
+        let splits_move = |&end: &usize| {
+            end < self.path.len()
+                && self.path[end - 1] == Step::Forward
+                && self.path[end] == Step::Forward
+        };
+        let (splits, whole): (Vec<usize>, Vec<usize>) =
+            fits.into_iter().rev().partition(splits_move);
+        whole.into_iter().chain(splits).collect()
+    }
+}
+
+#[test]
+fn test_compress_example() {
+    use Turn::*;
+
+    let path = vec![
+        (Right, 8),
+        (Right, 8),
+        (Right, 4),
+        (Right, 4),
+        (Right, 8),
+        (Left, 6),
+        (Left, 2),
+        (Right, 4),
+        (Right, 4),
+        (Right, 8),
+        (Right, 8),
+        (Right, 8),
+        (Left, 6),
+        (Left, 2),
+    ]
+    .into_iter()
+    .flat_map(|(t, n)| vec![Move::Turn(t), Move::Forward(n)])
+    .collect_vec();
+
+    let routines = Compressor::new().compress(&path).unwrap();
+    assert_eq!(routines.expand(), path);
+    assert!(routines.functions.len() <= 3);
+    assert!(routines.main_routine().len() <= 20);
+    assert!((0..routines.functions.len()).all(|f| routines.function(f).len() <= 20));
+
+    assert_eq!(
+        Compressor::new().with_functions(1).compress(&path),
+        Err(CompressError {
+            functions: 1,
+            line_limit: 20
+        })
+    );
+}
+
+#[test]
+fn test_compress_split_moves() {
+    // Only `10,L,10` three times fits: the 20 step runs have to be split between calls.
+    let path = vec![
+        Move::Forward(10),
+        Move::Turn(Turn::Left),
+        Move::Forward(20),
+        Move::Turn(Turn::Left),
+        Move::Forward(20),
+        Move::Turn(Turn::Left),
+        Move::Forward(10),
+    ];
+    let routines = Compressor::new()
+        .with_functions(1)
+        .with_line_limit(10)
+        .compress(&path)
+        .unwrap();
+    assert_eq!(routines.main_routine(), "A,A,A");
+    assert_eq!(routines.function(0), "10,L,10");
+    assert_eq!(routines.expand(), path);
+}
+
+#[test]
+fn test_parse_routines() {
+    let routines: Routines = "A,B,A\nR,8,L\n10\n".parse().unwrap();
+    assert_eq!(routines.main, vec![0, 1, 0]);
+    assert_eq!(
+        routines.functions,
+        vec![
+            vec![
+                Move::Turn(Turn::Right),
+                Move::Forward(8),
+                Move::Turn(Turn::Left)
+            ],
+            vec![Move::Forward(10)],
+        ]
+    );
+    assert_eq!(routines.function(0), "R,8,L");
+
+    let bad = "A,C\nR,8\nL,x".parse::<Routines>().unwrap_err();
+    assert_eq!((bad.line, bad.token.as_str()), (3, "x"));
+    let missing = "A,C\nR,8\nL".parse::<Routines>().unwrap_err();
+    assert_eq!((missing.line, missing.token.as_str()), (1, "C"));
+}
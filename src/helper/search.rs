@@ -0,0 +1,265 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::helper::{Grid, Point, NEIGHBOURS_4};
+
+/// Everything a search reached: the cheapest known cost to each node and how it got there.
+#[derive(Clone, Debug)]
+pub struct SearchTree<N: Eq + Hash> {
+    costs: HashMap<N, usize>,
+    parents: HashMap<N, N>,
+    /// The node that satisfied the goal and stopped the search, if any.
+    pub goal: Option<N>,
+}
+
+impl<N: Copy + Eq + Hash> SearchTree<N> {
+    fn new() -> Self {
+        SearchTree {
+            costs: HashMap::default(),
+            parents: HashMap::default(),
+            goal: None,
+        }
+    }
+
+    pub fn cost(&self, node: N) -> Option<usize> {
+        self.costs.get(&node).copied()
+    }
+
+    pub fn contains(&self, node: N) -> bool {
+        self.costs.contains_key(&node)
+    }
+
+    pub fn len(&self) -> usize {
+        self.costs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.costs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (N, usize)> + '_ {
+        self.costs.iter().map(|(&n, &c)| (n, c))
+    }
+
+    /// The reached node furthest from every start.
+    pub fn furthest(&self) -> Option<(N, usize)> {
+        self.iter().max_by_key(|&(_, c)| c)
+    }
+
+    /// The nodes from the start that reached `node` up to and including `node` itself.
+    pub fn path(&self, node: N) -> Option<Vec<N>> {
+        if !self.contains(node) {
+            return None;
+        }
+        let mut path = vec![node];
+        let mut curr = node;
+        while let Some(&prev) = self.parents.get(&curr) {
+            path.push(prev);
+            curr = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The path to the goal that stopped the search.
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.goal.and_then(|g| self.path(g))
+    }
+}
+
+/// Breadth-first search from every node in `starts` at once, stopping as soon as a node
+/// satisfying `is_goal` is reached. Pass `|_| false` to map out everything reachable.
+pub fn bfs<N, S, F, I, G>(starts: S, mut neighbours: F, mut is_goal: G) -> SearchTree<N>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = N>,
+    G: FnMut(N) -> bool,
+{
+    let mut tree = SearchTree::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if tree.costs.insert(start, 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(node) {
+            tree.goal = Some(node);
+            break;
+        }
+        let cost = tree.costs[&node] + 1;
+        for next in neighbours(node) {
+            if let Entry::Vacant(e) = tree.costs.entry(next) {
+                e.insert(cost);
+                tree.parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    tree
+}
+
+/// Dijkstra's algorithm, where `neighbours` yields each neighbour with the cost of the edge
+/// to it.
+pub fn dijkstra<N, S, F, I, G>(starts: S, neighbours: F, is_goal: G) -> SearchTree<N>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+    G: FnMut(N) -> bool,
+{
+    astar(starts, neighbours, |_| 0, is_goal)
+}
+
+/// A* search. `heuristic` must never overestimate the remaining cost to the goal, or the
+/// path found may not be the cheapest.
+pub fn astar<N, S, F, I, H, G>(
+    starts: S,
+    mut neighbours: F,
+    mut heuristic: H,
+    mut is_goal: G,
+) -> SearchTree<N>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+    H: FnMut(N) -> usize,
+    G: FnMut(N) -> bool,
+{
+    let mut tree = SearchTree::new();
+    // The heap holds indices into `nodes` so that `N` doesn't need to be `Ord`.
+    let mut nodes = vec![];
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        if tree.costs.insert(start, 0).is_none() {
+            heap.push(Reverse((heuristic(start), 0, nodes.len())));
+            nodes.push(start);
+        }
+    }
+
+    while let Some(Reverse((_, cost, idx))) = heap.pop() {
+        let node = nodes[idx];
+        if tree.costs[&node] < cost {
+            // A cheaper route was found after this entry was queued
+            continue;
+        }
+        if is_goal(node) {
+            tree.goal = Some(node);
+            break;
+        }
+        for (next, step) in neighbours(node) {
+            let next_cost = cost + step;
+            if tree.costs.get(&next).is_none_or(|&c| next_cost < c) {
+                tree.costs.insert(next, next_cost);
+                tree.parents.insert(next, node);
+                heap.push(Reverse((
+                    next_cost + heuristic(next),
+                    next_cost,
+                    nodes.len(),
+                )));
+                nodes.push(next);
+            }
+        }
+    }
+    tree
+}
+
+/// Admissible A* heuristic for unit steps between orthogonal neighbours.
+pub fn manhattan_to(goal: Point) -> impl Fn(Point) -> usize {
+    move |p| (goal - p).manhattan() as usize
+}
+
+/// Neighbour function for searching a grid, stepping orthogonally onto stored cells that
+/// are `passable`.
+pub fn grid_neighbours<'a, T, P>(
+    grid: &'a Grid<T>,
+    passable: P,
+) -> impl Fn(Point) -> Vec<Point> + 'a
+where
+    T: Clone,
+    P: Fn(&T) -> bool + 'a,
+{
+    move |p| {
+        grid.neighbours4(p)
+            .filter(|(_, t)| passable(t))
+            .map(|(n, _)| n)
+            .collect()
+    }
+}
+
+/// Shortest orthogonal path across a grid using A*.
+pub fn grid_path<T, P>(grid: &Grid<T>, passable: P, start: Point, goal: Point) -> Option<Vec<Point>>
+where
+    T: Clone,
+    P: Fn(&T) -> bool,
+{
+    let neighbours = grid_neighbours(grid, passable);
+    astar(
+        std::iter::once(start),
+        |p| neighbours(p).into_iter().map(|n| (n, 1)),
+        manhattan_to(goal),
+        |p| p == goal,
+    )
+    .goal_path()
+}
+
+/// Orthogonal neighbours of `p`, clockwise from north, for searches without a grid.
+pub fn orthogonal(p: Point) -> impl Iterator<Item = Point> {
+    NEIGHBOURS_4.iter().map(move |&d| p + d)
+}
+
+#[test]
+fn test_bfs() {
+    let grid: Grid<char> = Grid::from_rows(
+        "#####\n#..##\n#.#.#\n#...#\n#####"
+            .lines()
+            .map(|l| l.chars().collect())
+            .collect(),
+    )
+    .unwrap();
+    let open = grid_neighbours(&grid, |&c| c == '.');
+
+    let all = bfs(std::iter::once(Point::new(1, 1)), &open, |_| false);
+    assert_eq!(all.len(), 7);
+    assert_eq!(all.furthest(), Some((Point::new(3, 2), 5)));
+    assert_eq!(all.cost(Point::new(2, 3)), Some(3));
+    assert_eq!(all.cost(Point::new(0, 0)), None);
+
+    let early = bfs(std::iter::once(Point::new(1, 1)), &open, |p| p.y() == 3);
+    assert_eq!(early.goal, Some(Point::new(1, 3)));
+    assert_eq!(early.goal_path().unwrap().len(), 3);
+    assert!(!early.contains(Point::new(3, 2)));
+
+    let multi = bfs(vec![Point::new(1, 1), Point::new(3, 2)], &open, |_| false);
+    assert_eq!(multi.furthest().map(|(_, c)| c), Some(2));
+}
+
+#[test]
+fn test_weighted_search() {
+    // Going round 0 -> 1 -> 2 -> 3 is cheaper than the direct edge 0 -> 3.
+    let edges = |n: usize| match n {
+        0 => vec![(1, 1), (3, 10)],
+        1 => vec![(2, 1)],
+        2 => vec![(3, 1)],
+        _ => vec![],
+    };
+    let tree = dijkstra(std::iter::once(0), edges, |n| n == 3);
+    assert_eq!(tree.cost(3), Some(3));
+    assert_eq!(tree.goal_path(), Some(vec![0, 1, 2, 3]));
+
+    let mut grid = Grid::new(10, 10, true);
+    for y in 0..9 {
+        grid.set(Point::new(5, y), false);
+    }
+    let path = grid_path(&grid, |&open| open, Point::new(0, 0), Point::new(9, 0)).unwrap();
+    assert_eq!(path.len(), 28);
+    assert!(path.iter().all(|p| grid[*p]));
+    assert!(grid_path(&grid, |&open| open, Point::new(0, 0), Point::new(5, 0)).is_none());
+}
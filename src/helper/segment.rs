@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use crate::helper::{Heading, Point, Rect};
+
+/// A horizontal or vertical run of a wire, including both ends.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub start: Point,
+    pub end: Point,
+    /// How far along the wire `start` is.
+    pub steps: i64,
+}
+
+impl Segment {
+    pub fn bounds(&self) -> Rect {
+        Rect::new(self.start, self.end)
+    }
+
+    /// Steps along the wire to reach `p`, which must be on this segment.
+    pub fn steps_to(&self, p: Point) -> i64 {
+        self.steps + (p - self.start).manhattan()
+    }
+
+    /// Every point on both segments. Crossing segments share one point; collinear segments
+    /// can overlap along a run of points.
+    pub fn intersection(&self, other: &Segment) -> impl Iterator<Item = Point> {
+        self.bounds()
+            .intersection(&other.bounds())
+            .into_iter()
+            .flat_map(|r| r.points())
+    }
+}
+
+/// A wire laid out as a chain of axis-aligned segments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Wire {
+    pub segments: Vec<Segment>,
+}
+
+impl Wire {
+    /// Lays out a wire from `start`, moving `n` steps in each heading in turn.
+    pub fn from_moves<I: IntoIterator<Item = (Heading, usize)>>(start: Point, moves: I) -> Self {
+        let mut segments = vec![];
+        let (mut pos, mut steps) = (start, 0);
+        for (heading, n) in moves {
+            let end = pos + heading.delta() * n as i64;
+            segments.push(Segment {
+                start: pos,
+                end,
+                steps,
+            });
+            pos = end;
+            steps += n as i64;
+        }
+        Wire { segments }
+    }
+
+    pub fn len(&self) -> i64 {
+        self.segments
+            .last()
+            .map_or(0, |s| s.steps + (s.end - s.start).manhattan())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A point where two wires meet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Crossing {
+    pub point: Point,
+    /// Indices of the two wires.
+    pub wires: (usize, usize),
+    /// The fewest steps each wire takes to first reach `point`.
+    pub steps: (i64, i64),
+}
+
+/// Every point where a pair of wires meet, other than a point where both wires start, even if
+/// the wires pass back through it later.
+///
+/// Each pair of wires is reported separately, so a point shared by three wires gives three
+/// crossings.
+pub fn crossings(wires: &[Wire]) -> Vec<Crossing> {
+    let start = |w: &Wire| w.segments.first().map(|s| s.start);
+    let mut found: HashMap<(Point, usize, usize), (i64, i64)> = HashMap::default();
+    for (i, a) in wires.iter().enumerate() {
+        for (j, b) in wires.iter().enumerate().skip(i + 1) {
+            let shared_start = start(a).filter(|&p| Some(p) == start(b));
+            for sa in a.segments.iter() {
+                for sb in b.segments.iter() {
+                    for p in sa.intersection(sb).filter(|&p| Some(p) != shared_start) {
+                        let steps = (sa.steps_to(p), sb.steps_to(p));
+                        let best = found.entry((p, i, j)).or_insert(steps);
+                        *best = (best.0.min(steps.0), best.1.min(steps.1));
+                    }
+                }
+            }
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|((point, i, j), steps)| Crossing {
+            point,
+            wires: (i, j),
+            steps,
+        })
+        .collect()
+}
+
+#[test]
+fn test_crossings() {
+    use Heading::*;
+
+    let origin = Point::new(0, 0);
+    let a = Wire::from_moves(origin, vec![(East, 8), (North, 5), (West, 5), (South, 3)]);
+    let b = Wire::from_moves(origin, vec![(North, 7), (East, 6), (South, 4), (West, 4)]);
+    assert_eq!(a.len(), 21);
+
+    let mut found = crossings(&[a.clone(), b.clone()]);
+    found.sort_by_key(|c| c.point.manhattan());
+    let points: Vec<_> = found.iter().map(|c| c.point).collect();
+    assert_eq!(points, vec![Point::new(3, -3), Point::new(6, -5)]);
+    assert_eq!(found[0].steps, (20, 20));
+    assert_eq!(found[1].steps, (15, 15));
+
+    // Runs along the bottom of `a`, overlapping it for three points.
+    let c = Wire::from_moves(Point::new(-2, 0), vec![(East, 4)]);
+    let overlap = crossings(&[a, b, c]);
+    let with_c: Vec<_> = overlap.iter().filter(|c| c.wires == (0, 2)).collect();
+    assert_eq!(with_c.len(), 3);
+    assert!(with_c.iter().all(|c| c.steps.1 == c.point.x() + 2));
+    assert!(overlap.iter().any(|c| c.wires == (1, 2)));
+}
+
+#[test]
+fn test_crossings_through_start() {
+    use Heading::*;
+
+    // `b` loops back through the shared start and then runs along `a`. The start still isn't a
+    // crossing, but the rest of the overlap is.
+    let origin = Point::new(0, 0);
+    let a = Wire::from_moves(origin, vec![(East, 5), (North, 2)]);
+    let b = Wire::from_moves(
+        origin,
+        vec![(North, 3), (West, 2), (South, 3), (East, 4), (North, 5)],
+    );
+    let found = crossings(&[a, b]);
+    assert!(found.iter().all(|c| c.point != origin), "{:?}", found);
+    let mut points: Vec<_> = found.iter().map(|c| c.point).collect();
+    points.sort_by_key(|p| p.x());
+    assert_eq!(points, vec![Point::new(1, 0), Point::new(2, 0)]);
+}
@@ -0,0 +1,335 @@
+use num::{Integer, Num, Signed, ToPrimitive};
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
+
+/// An `N`-dimensional vector of numbers, used for both positions and offsets.
+///
+/// Two, three and four dimensional vectors also have named accessors for their components
+/// (`p.x()`, `p.y_mut()`, ...).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Vector<T, const N: usize> {
+    pub v: [T; N],
+}
+
+pub type Point = Vector<i64, 2>;
+pub type Point3 = Vector<i64, 3>;
+pub type Point4 = Vector<i64, 4>;
+
+impl<T, const N: usize> Vector<T, N> {
+    pub const fn from_array(v: [T; N]) -> Self {
+        Vector { v }
+    }
+
+    pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> Vector<U, N> {
+        Vector { v: self.v.map(f) }
+    }
+
+    fn zip<U, F: FnMut(T, T) -> U>(self, rhs: Self, mut f: F) -> Vector<U, N>
+    where
+        T: Copy,
+    {
+        Vector {
+            v: std::array::from_fn(|i| f(self.v[i], rhs.v[i])),
+        }
+    }
+}
+
+impl<T: Copy + Num, const N: usize> Vector<T, N> {
+    pub fn zero() -> Self {
+        Vector { v: [T::zero(); N] }
+    }
+
+    pub fn dot(&self, rhs: &Self) -> T {
+        (0..N).fold(T::zero(), |acc, i| acc + self.v[i] * rhs.v[i])
+    }
+}
+
+impl<T: Copy + Signed, const N: usize> Vector<T, N> {
+    /// Manhattan distance from the origin.
+    pub fn manhattan(&self) -> T {
+        self.v.iter().fold(T::zero(), |acc, c| acc + c.abs())
+    }
+
+    pub fn signum(&self) -> Self {
+        self.map(|c| c.signum())
+    }
+}
+
+impl<T: Copy + Signed + PartialOrd, const N: usize> Vector<T, N> {
+    /// The largest distance along any one axis, i.e. the number of king's moves apart.
+    pub fn chebyshev(&self, rhs: &Self) -> T {
+        (*self - *rhs)
+            .v
+            .iter()
+            .map(|c| c.abs())
+            .fold(T::zero(), |acc, c| if c > acc { c } else { acc })
+    }
+}
+
+impl<T: Copy + Sub<Output = T> + ToPrimitive, const N: usize> Vector<T, N> {
+    pub fn euclidean(&self, rhs: &Self) -> f64 {
+        self.v
+            .iter()
+            .zip(rhs.v.iter())
+            .map(|(&a, &b)| (a - b).to_f64().unwrap_or(f64::NAN).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+impl<T: Copy + PartialOrd, const N: usize> Vector<T, N> {
+    /// Component-wise minimum.
+    pub fn min(&self, rhs: &Self) -> Self {
+        self.zip(*rhs, |a, b| if b < a { b } else { a })
+    }
+
+    /// Component-wise maximum.
+    pub fn max(&self, rhs: &Self) -> Self {
+        self.zip(*rhs, |a, b| if b > a { b } else { a })
+    }
+}
+
+impl<T: Copy + Integer, const N: usize> Vector<T, N> {
+    /// Divides every component by their greatest common divisor, giving the smallest step
+    /// in the same direction. The zero vector is returned unchanged.
+    pub fn reduced(&self) -> Self {
+        let gcd = self.v.iter().fold(T::zero(), |acc, c| acc.gcd(c));
+        if gcd.is_zero() {
+            *self
+        } else {
+            self.map(|c| c / gcd)
+        }
+    }
+}
+
+impl<T> Vector<T, 2> {
+    pub const fn new(x: T, y: T) -> Self {
+        Vector { v: [x, y] }
+    }
+}
+
+impl<T: Copy + Num> Vector<T, 2> {
+    /// The z component of the 3D cross product; positive when `rhs` is clockwise of `self`
+    /// with `y` pointing down.
+    pub fn cross(&self, rhs: &Self) -> T {
+        self.v[0] * rhs.v[1] - self.v[1] * rhs.v[0]
+    }
+}
+
+impl<T> Vector<T, 3> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Vector { v: [x, y, z] }
+    }
+}
+
+impl<T: Copy + Num> Vector<T, 3> {
+    pub fn cross(&self, rhs: &Self) -> Self {
+        let [a, b, c] = self.v;
+        let [d, e, f] = rhs.v;
+        Vector::<T, 3>::new(b * f - c * e, c * d - a * f, a * e - b * d)
+    }
+}
+
+impl<T> Vector<T, 4> {
+    pub const fn new(x: T, y: T, z: T, w: T) -> Self {
+        Vector { v: [x, y, z, w] }
+    }
+}
+
+/// Named accessors for the components of small vectors.
+macro_rules! impl_accessors {
+    ($n:expr, $(($get:ident, $get_mut:ident, $idx:expr)),*) => {
+        impl<T: Copy> Vector<T, $n> {
+            $(
+                pub fn $get(&self) -> T {
+                    self.v[$idx]
+                }
+            )*
+            $(
+                pub fn $get_mut(&mut self) -> &mut T {
+                    &mut self.v[$idx]
+                }
+            )*
+        }
+    };
+}
+
+impl_accessors!(2, (x, x_mut, 0), (y, y_mut, 1));
+impl_accessors!(3, (x, x_mut, 0), (y, y_mut, 1), (z, z_mut, 2));
+impl_accessors!(
+    4,
+    (x, x_mut, 0),
+    (y, y_mut, 1),
+    (z, z_mut, 2),
+    (w, w_mut, 3)
+);
+
+impl<T: Copy + Add<Output = T>, const N: usize> Add for Vector<T, N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip(rhs, |a, b| a + b)
+    }
+}
+impl<T: Copy + AddAssign, const N: usize> AddAssign for Vector<T, N> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (a, b) in self.v.iter_mut().zip(rhs.v.iter()) {
+            *a += *b;
+        }
+    }
+}
+impl<T: Copy + Sub<Output = T>, const N: usize> Sub for Vector<T, N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip(rhs, |a, b| a - b)
+    }
+}
+impl<T: Copy + SubAssign, const N: usize> SubAssign for Vector<T, N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (a, b) in self.v.iter_mut().zip(rhs.v.iter()) {
+            *a -= *b;
+        }
+    }
+}
+impl<T: Copy + Mul<Output = T>, const N: usize> Mul<T> for Vector<T, N> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.map(|a| a * rhs)
+    }
+}
+impl<T: Copy + MulAssign, const N: usize> MulAssign<T> for Vector<T, N> {
+    fn mul_assign(&mut self, rhs: T) {
+        for a in self.v.iter_mut() {
+            *a *= rhs;
+        }
+    }
+}
+impl<T: Copy + Div<Output = T>, const N: usize> Div<T> for Vector<T, N> {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        self.map(|a| a / rhs)
+    }
+}
+impl<T: Copy + DivAssign, const N: usize> DivAssign<T> for Vector<T, N> {
+    fn div_assign(&mut self, rhs: T) {
+        for a in self.v.iter_mut() {
+            *a /= rhs;
+        }
+    }
+}
+impl<T: Neg<Output = T>, const N: usize> Neg for Vector<T, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.map(|a| -a)
+    }
+}
+
+macro_rules! impl_scalar_mul {
+    ($($t:ty),*) => {
+        $(
+            impl<const N: usize> Mul<Vector<$t, N>> for $t {
+                type Output = Vector<$t, N>;
+
+                fn mul(self, rhs: Vector<$t, N>) -> Self::Output {
+                    rhs * self
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar_mul!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl<T, const N: usize> Index<usize> for Vector<T, N> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        &self.v[idx]
+    }
+}
+impl<T, const N: usize> IndexMut<usize> for Vector<T, N> {
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        &mut self.v[idx]
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vector<T, N> {
+    fn from(v: [T; N]) -> Self {
+        Vector { v }
+    }
+}
+impl<T> From<(T, T)> for Vector<T, 2> {
+    fn from((x, y): (T, T)) -> Self {
+        Vector { v: [x, y] }
+    }
+}
+impl<T> From<Vector<T, 2>> for (T, T) {
+    fn from(p: Vector<T, 2>) -> Self {
+        let [x, y] = p.v;
+        (x, y)
+    }
+}
+impl<T> From<(T, T, T)> for Vector<T, 3> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Vector { v: [x, y, z] }
+    }
+}
+impl<T> From<Vector<T, 3>> for (T, T, T) {
+    fn from(p: Vector<T, 3>) -> Self {
+        let [x, y, z] = p.v;
+        (x, y, z)
+    }
+}
+
+#[test]
+fn test_vector_fields() {
+    let mut p = Point::new(3, -4);
+    assert_eq!((p.x(), p.y()), (3, -4));
+    *p.x_mut() += 2;
+    *p.y_mut() = 1;
+    assert_eq!(p, Point::new(5, 1));
+    assert_eq!(p[0], 5);
+    assert_eq!(<(i64, i64)>::from(p), (5, 1));
+
+    let mut q = Point3::new(1, 2, 3);
+    *q.z_mut() = 7;
+    q[0] = -1;
+    assert_eq!((q.x(), q.y(), q.z()), (-1, 2, 7));
+
+    let r = Point4::new(1, 2, 3, 4);
+    assert_eq!(r.w(), 4);
+}
+
+#[test]
+fn test_vector_ops() {
+    let a = Point::new(6, -4);
+    let b = Point::new(1, 2);
+    assert_eq!(a + b, Point::new(7, -2));
+    assert_eq!(a - b, Point::new(5, -6));
+    assert_eq!(2 * a, a * 2);
+    assert_eq!(-a / 2, Point::new(-3, 2));
+    assert_eq!(a.manhattan(), 10);
+    assert_eq!(a.dot(&b), -2);
+    assert_eq!(a.cross(&b), 16);
+    assert_eq!(a.chebyshev(&b), 6);
+    assert_eq!(a.min(&b), Point::new(1, -4));
+    assert_eq!(a.max(&b), Point::new(6, 2));
+    assert_eq!(a.signum(), Point::new(1, -1));
+    assert_eq!(a.reduced(), Point::new(3, -2));
+    assert_eq!(Point::zero().reduced(), Point::zero());
+
+    let x = Point3::new(1, 0, 0);
+    let y = Point3::new(0, 1, 0);
+    assert_eq!(x.cross(&y), Point3::new(0, 0, 1));
+
+    let f = Vector::<f64, 2>::new(0.0, 0.0);
+    let g = Vector::<f64, 2>::new(3.0, 4.0);
+    assert_eq!(f.euclidean(&g), 5.0);
+    assert_eq!((g * 0.5).x(), 1.5);
+    assert_eq!(Point::new(0, 0).euclidean(&Point::new(-3, 4)), 5.0);
+}
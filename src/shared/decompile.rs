@@ -0,0 +1,727 @@
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::shared::{Instruction, Opcodes, ParameterModes};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Imm(i64),
+    Mem(i64),
+    /// A stack slot, as an offset from the relative base on entry to the function
+    Slot(i64),
+    /// A relative operand whose base couldn't be tracked statically
+    Rel(i64),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Cond {
+    Always,
+    NonZero(Operand),
+    Zero(Operand),
+}
+
+impl Cond {
+    fn negate(self) -> Cond {
+        match self {
+            Cond::Always => Cond::Always,
+            Cond::NonZero(o) => Cond::Zero(o),
+            Cond::Zero(o) => Cond::NonZero(o),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stmt {
+    Assign {
+        dst: Operand,
+        op: Opcodes,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    Input(Operand),
+    Output(Operand),
+    AdjustBase(Operand),
+    Call(usize),
+    Return(Cond),
+    IndirectJump(Cond, Operand),
+    Goto(Cond, usize),
+    Halt,
+    If {
+        cond: Cond,
+        then: Vec<Line>,
+        otherwise: Vec<Line>,
+    },
+    While {
+        cond: Cond,
+        body: Vec<Line>,
+    },
+    DoWhile {
+        body: Vec<Line>,
+        cond: Cond,
+    },
+    Invalid(i64),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub addr: usize,
+    pub stmt: Stmt,
+}
+
+pub struct Function {
+    pub entry: usize,
+    pub body: Vec<Line>,
+    slot_names: HashMap<i64, String>,
+}
+
+pub struct Decompiled {
+    pub functions: Vec<Function>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Flow {
+    Next,
+    Never,
+    Goto(Cond, usize),
+    Call(usize),
+    Return(Cond),
+    Indirect(Cond, Operand),
+    Halt,
+    Invalid,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Decoded {
+    instruction: Option<Instruction>,
+    raw: i64,
+    params: [Operand; 3],
+    flow: Flow,
+    size: usize,
+}
+
+struct Disassembler<'a> {
+    data: &'a [i64],
+    functions: BTreeSet<usize>,
+}
+
+impl<'a> Disassembler<'a> {
+    fn get(&self, addr: usize) -> i64 {
+        self.data.get(addr).copied().unwrap_or(0)
+    }
+
+    fn decode(&self, addr: usize, delta: Option<i64>) -> Decoded {
+        let raw = self.get(addr);
+        let instruction = match Instruction::decode(raw) {
+            Some(i) => i,
+            None => {
+                return Decoded {
+                    instruction: None,
+                    raw,
+                    params: [Operand::Imm(0); 3],
+                    flow: Flow::Invalid,
+                    size: 1,
+                }
+            }
+        };
+        let mut params = [Operand::Imm(0); 3];
+        for (idx, p) in params
+            .iter_mut()
+            .enumerate()
+            .take(instruction.opcode.param_count())
+        {
+            let val = self.get(addr + 1 + idx);
+            *p = match instruction.modes[idx] {
+                ParameterModes::Immediate => Operand::Imm(val),
+                ParameterModes::Position => Operand::Mem(val),
+                ParameterModes::Relative => match delta.and_then(|d| d.checked_add(val)) {
+                    Some(slot) => Operand::Slot(slot),
+                    None => Operand::Rel(val),
+                },
+            };
+        }
+
+        let flow = match instruction.opcode {
+            Opcodes::Halt => Flow::Halt,
+            Opcodes::JumpIfTrue | Opcodes::JumpIfFalse => {
+                let jump_if_true = instruction.opcode == Opcodes::JumpIfTrue;
+                let cond = match params[0] {
+                    Operand::Imm(v) if (v != 0) == jump_if_true => Cond::Always,
+                    Operand::Imm(_) => return self.plain(instruction, raw, params, Flow::Never),
+                    o if jump_if_true => Cond::NonZero(o),
+                    o => Cond::Zero(o),
+                };
+                match params[1] {
+                    Operand::Imm(t) => Flow::Goto(cond, t as usize),
+                    // Assume code doesn't overwrite its own jump tables
+                    Operand::Mem(a) => Flow::Goto(cond, self.get(a as usize) as usize),
+                    Operand::Slot(_) => Flow::Return(cond),
+                    o => Flow::Indirect(cond, o),
+                }
+            }
+            _ => Flow::Next,
+        };
+        self.plain(instruction, raw, params, flow)
+    }
+
+    fn plain(
+        &self,
+        instruction: Instruction,
+        raw: i64,
+        params: [Operand; 3],
+        flow: Flow,
+    ) -> Decoded {
+        Decoded {
+            instruction: Some(instruction),
+            raw,
+            params,
+            flow,
+            size: instruction.size(),
+        }
+    }
+
+    /// Recovers the instructions of the function starting at `entry`, along with the relative
+    /// base offset in effect at each one. Call targets are queued as new functions.
+    fn function(&mut self, entry: usize) -> BTreeMap<usize, (Decoded, Option<i64>)> {
+        let mut insns: BTreeMap<usize, (Decoded, Option<i64>)> = BTreeMap::new();
+        let mut fallthrough_from: HashMap<usize, usize> = HashMap::default();
+        let mut worklist = vec![(entry, Some(0))];
+
+        while let Some((addr, delta)) = worklist.pop() {
+            if insns.contains_key(&addr) || addr >= self.data.len() {
+                continue;
+            }
+            let mut decoded = self.decode(addr, delta);
+            let next = addr + decoded.size;
+
+            match decoded.flow {
+                Flow::Next | Flow::Never => {
+                    let delta = match (decoded.instruction, decoded.params[0]) {
+                        (Some(i), Operand::Imm(v)) if i.opcode == Opcodes::RelativeBaseOffset => {
+                            delta.and_then(|d| d.checked_add(v))
+                        }
+                        (Some(i), _) if i.opcode == Opcodes::RelativeBaseOffset => None,
+                        _ => delta,
+                    };
+                    fallthrough_from.insert(next, addr);
+                    worklist.push((next, delta));
+                }
+                Flow::Goto(Cond::Always, target) => {
+                    if self.is_call(&insns, &fallthrough_from, addr, next) {
+                        decoded.flow = Flow::Call(target);
+                        self.functions.insert(target);
+                        fallthrough_from.insert(next, addr);
+                        worklist.push((next, delta));
+                    } else {
+                        worklist.push((target, delta));
+                    }
+                }
+                Flow::Goto(_, target) => {
+                    fallthrough_from.insert(next, addr);
+                    worklist.push((next, delta));
+                    worklist.push((target, delta));
+                }
+                Flow::Return(Cond::Always) | Flow::Indirect(Cond::Always, _) => {}
+                Flow::Return(_) | Flow::Indirect(_, _) => {
+                    fallthrough_from.insert(next, addr);
+                    worklist.push((next, delta));
+                }
+                Flow::Call(_) | Flow::Halt | Flow::Invalid => {}
+            }
+            insns.insert(addr, (decoded, delta));
+        }
+        insns
+    }
+
+    /// An unconditional jump is a call if the straight-line code leading up to it stores the
+    /// address following the jump into a stack slot.
+    fn is_call(
+        &self,
+        insns: &BTreeMap<usize, (Decoded, Option<i64>)>,
+        fallthrough_from: &HashMap<usize, usize>,
+        addr: usize,
+        ret: usize,
+    ) -> bool {
+        let mut addr = addr;
+        for _ in 0..4 {
+            addr = match fallthrough_from.get(&addr) {
+                Some(&a) => a,
+                None => return false,
+            };
+            let (d, _) = &insns[&addr];
+            let op = d.instruction.map(|i| i.opcode);
+            let value = match (op, d.params[0], d.params[1]) {
+                (Some(Opcodes::Addition), Operand::Imm(a), Operand::Imm(b)) => a.checked_add(b),
+                (Some(Opcodes::Multiplication), Operand::Imm(a), Operand::Imm(b)) => {
+                    a.checked_mul(b)
+                }
+                _ => None,
+            };
+            if value == Some(ret as i64) {
+                if let Operand::Slot(_) | Operand::Rel(_) = d.params[2] {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+struct Structurer {
+    addrs: Vec<usize>,
+    index: HashMap<usize, usize>,
+    insns: BTreeMap<usize, (Decoded, Option<i64>)>,
+}
+
+impl Structurer {
+    fn flow(&self, idx: usize) -> Flow {
+        self.insns[&self.addrs[idx]].0.flow
+    }
+
+    fn end_addr(&self, idx: usize) -> usize {
+        self.addrs.get(idx).copied().unwrap_or(usize::MAX)
+    }
+
+    /// Index of the instruction at `target`, if it lies within `lo..=hi`.
+    fn index_in(&self, target: usize, lo: usize, hi: usize) -> Option<usize> {
+        if target == self.end_addr(hi) {
+            return Some(hi);
+        }
+        self.index
+            .get(&target)
+            .copied()
+            .filter(|&i| lo <= i && i <= hi)
+    }
+
+    /// True if no instruction in `lo..hi` jumps outside of `lo..=hi`.
+    fn contained(&self, lo: usize, hi: usize) -> bool {
+        (lo..hi).all(|i| match self.flow(i) {
+            Flow::Goto(_, t) => self.index_in(t, lo, hi).is_some(),
+            _ => true,
+        })
+    }
+
+    fn build(&self, lo: usize, hi: usize, skip_loop_at: Option<usize>) -> Vec<Line> {
+        let mut lines = vec![];
+        let mut i = lo;
+        while i < hi {
+            let addr = self.addrs[i];
+
+            // Loops: the last jump back to this instruction closes the loop
+            let back_edge = (i..hi)
+                .rev()
+                .find(|&e| matches!(self.flow(e), Flow::Goto(_, t) if t == addr));
+            if let (Some(e), true) = (back_edge, skip_loop_at != Some(i)) {
+                let stmt = match (self.flow(i), self.flow(e)) {
+                    (Flow::Goto(c, exit), Flow::Goto(Cond::Always, _))
+                        if e > i && c != Cond::Always && exit == self.end_addr(e + 1) =>
+                    {
+                        Stmt::While {
+                            cond: c.negate(),
+                            body: self.build(i + 1, e, None),
+                        }
+                    }
+                    (_, Flow::Goto(Cond::Always, _)) => Stmt::While {
+                        cond: Cond::Always,
+                        body: self.build(i, e, Some(i)),
+                    },
+                    (_, Flow::Goto(c, _)) => Stmt::DoWhile {
+                        body: self.build(i, e, Some(i)),
+                        cond: c,
+                    },
+                    _ => unreachable!(),
+                };
+                lines.push(Line { addr, stmt });
+                i = e + 1;
+                continue;
+            }
+
+            // Conditionals: a forward jump over a self-contained region
+            if let Flow::Goto(cond, target) = self.flow(i) {
+                if cond != Cond::Always && target > addr {
+                    if let Some(j) = self.index_in(target, i + 1, hi) {
+                        let else_branch = match self.flow(j - 1) {
+                            Flow::Goto(Cond::Always, end) if j - 1 > i && end > target => {
+                                self.index_in(end, j, hi)
+                            }
+                            _ => None,
+                        };
+                        if let Some(k) = else_branch {
+                            if self.contained(i + 1, j - 1) && self.contained(j, k) {
+                                lines.push(Line {
+                                    addr,
+                                    stmt: Stmt::If {
+                                        cond: cond.negate(),
+                                        then: self.build(i + 1, j - 1, None),
+                                        otherwise: self.build(j, k, None),
+                                    },
+                                });
+                                i = k;
+                                continue;
+                            }
+                        }
+                        if self.contained(i + 1, j) {
+                            lines.push(Line {
+                                addr,
+                                stmt: Stmt::If {
+                                    cond: cond.negate(),
+                                    then: self.build(i + 1, j, None),
+                                    otherwise: vec![],
+                                },
+                            });
+                            i = j;
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            if let Some(stmt) = self.simple(i) {
+                lines.push(Line { addr, stmt });
+            }
+            i += 1;
+        }
+        lines
+    }
+
+    fn simple(&self, idx: usize) -> Option<Stmt> {
+        let (d, _) = &self.insns[&self.addrs[idx]];
+        let [p1, p2, p3] = d.params;
+        let opcode = match d.instruction {
+            Some(i) => i.opcode,
+            None => return Some(Stmt::Invalid(d.raw)),
+        };
+        Some(match d.flow {
+            Flow::Never => return None,
+            Flow::Goto(c, t) => Stmt::Goto(c, t),
+            Flow::Call(t) => Stmt::Call(t),
+            Flow::Return(c) => Stmt::Return(c),
+            Flow::Indirect(c, o) => Stmt::IndirectJump(c, o),
+            Flow::Halt => Stmt::Halt,
+            Flow::Invalid => Stmt::Invalid(d.raw),
+            Flow::Next => match opcode {
+                Opcodes::Input => Stmt::Input(p1),
+                Opcodes::Output => Stmt::Output(p1),
+                Opcodes::RelativeBaseOffset => Stmt::AdjustBase(p1),
+                op => Stmt::Assign {
+                    dst: p3,
+                    op,
+                    lhs: p1,
+                    rhs: p2,
+                },
+            },
+        })
+    }
+}
+
+/// Names stack slots: `ret` for the return address, `argN` for slots read before the function
+/// writes them, and `localN` for the rest.
+fn name_slots(insns: &BTreeMap<usize, (Decoded, Option<i64>)>) -> HashMap<i64, String> {
+    let mut names = HashMap::default();
+    let mut written: HashSet<i64> = HashSet::default();
+    let mut args = BTreeSet::new();
+    let mut locals = BTreeSet::new();
+
+    for (d, _) in insns.values() {
+        if let Flow::Return(_) = d.flow {
+            if let Operand::Slot(s) = d.params[1] {
+                names.insert(s, "ret".to_owned());
+            }
+        }
+        let count = d.instruction.map_or(0, |i| i.opcode.param_count());
+        let writes = match d.instruction.map(|i| i.opcode) {
+            Some(Opcodes::Input) => Some(0),
+            Some(Opcodes::Addition)
+            | Some(Opcodes::Multiplication)
+            | Some(Opcodes::LessThan)
+            | Some(Opcodes::Equals) => Some(2),
+            _ => None,
+        };
+        for (idx, p) in d.params.iter().enumerate().take(count) {
+            if let Operand::Slot(s) = *p {
+                if Some(idx) == writes {
+                    written.insert(s);
+                    locals.insert(s);
+                } else if !written.contains(&s) {
+                    args.insert(s);
+                }
+            }
+        }
+    }
+    let args = args
+        .into_iter()
+        .filter(|s| !names.contains_key(s))
+        .collect_vec();
+    let locals = locals
+        .into_iter()
+        .filter(|s| !names.contains_key(s) && !args.contains(s))
+        .collect_vec();
+    for (n, s) in args.into_iter().enumerate() {
+        names.insert(s, format!("arg{}", n));
+    }
+    for (n, s) in locals.into_iter().enumerate() {
+        names.insert(s, format!("local{}", n));
+    }
+    names
+}
+
+pub fn decompile(data: &[i64]) -> Decompiled {
+    let mut disassembler = Disassembler {
+        data,
+        functions: BTreeSet::new(),
+    };
+    disassembler.functions.insert(0);
+
+    let mut done = BTreeSet::new();
+    let mut functions = vec![];
+    while let Some(&entry) = disassembler.functions.difference(&done).next() {
+        done.insert(entry);
+        let insns = disassembler.function(entry);
+        let addrs: Vec<usize> = insns.keys().copied().collect();
+        let structurer = Structurer {
+            index: addrs.iter().enumerate().map(|(i, &a)| (a, i)).collect(),
+            addrs,
+            insns,
+        };
+        functions.push(Function {
+            entry,
+            body: structurer.build(0, structurer.addrs.len(), None),
+            slot_names: name_slots(&structurer.insns),
+        });
+    }
+
+    Decompiled { functions }
+}
+
+impl Function {
+    fn operand(&self, o: Operand) -> String {
+        match o {
+            Operand::Imm(v) => format!("{}", v),
+            Operand::Mem(a) => format!("mem[{}]", a),
+            Operand::Slot(s) => self
+                .slot_names
+                .get(&s)
+                .cloned()
+                .unwrap_or_else(|| format!("stack[{}]", s)),
+            Operand::Rel(r) => format!("mem[rb + {}]", r),
+        }
+    }
+
+    fn cond(&self, c: Cond) -> String {
+        match c {
+            Cond::Always => "true".to_owned(),
+            Cond::NonZero(o) => format!("{} != 0", self.operand(o)),
+            Cond::Zero(o) => format!("{} == 0", self.operand(o)),
+        }
+    }
+
+    fn labels(lines: &[Line], labels: &mut HashSet<usize>) {
+        for line in lines {
+            match &line.stmt {
+                Stmt::Goto(_, t) => {
+                    labels.insert(*t);
+                }
+                Stmt::If {
+                    then, otherwise, ..
+                } => {
+                    Function::labels(then, labels);
+                    Function::labels(otherwise, labels);
+                }
+                Stmt::While { body, .. } | Stmt::DoWhile { body, .. } => {
+                    Function::labels(body, labels)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn write_lines(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        lines: &[Line],
+        depth: usize,
+        labels: &HashSet<usize>,
+    ) -> std::fmt::Result {
+        let indent = "    ".repeat(depth);
+        for line in lines {
+            if labels.contains(&line.addr) {
+                writeln!(
+                    f,
+                    "{:>6}    {}L{}:",
+                    "",
+                    "    ".repeat(depth - 1),
+                    line.addr
+                )?;
+            }
+            // I/O is marked in the gutter so it stands out when skimming
+            let (io, text) = match &line.stmt {
+                Stmt::Input(o) => (true, format!("{} = input();", self.operand(*o))),
+                Stmt::Output(o) => (true, format!("output({});", self.operand(*o))),
+                Stmt::Assign { dst, op, lhs, rhs } => {
+                    let (lhs, rhs) = (self.operand(*lhs), self.operand(*rhs));
+                    let expr = match op {
+                        // Moves are usually written as `x + 0` or `x * 1`
+                        Opcodes::Addition if lhs == "0" => rhs,
+                        Opcodes::Addition if rhs == "0" => lhs,
+                        Opcodes::Multiplication if lhs == "1" => rhs,
+                        Opcodes::Multiplication if rhs == "1" => lhs,
+                        Opcodes::Addition => format!("{} + {}", lhs, rhs),
+                        Opcodes::Multiplication => format!("{} * {}", lhs, rhs),
+                        Opcodes::LessThan => format!("{} < {}", lhs, rhs),
+                        _ => format!("{} == {}", lhs, rhs),
+                    };
+                    (false, format!("{} = {};", self.operand(*dst), expr))
+                }
+                Stmt::AdjustBase(o) => (false, format!("rb += {};", self.operand(*o))),
+                Stmt::Call(t) => (false, format!("call fn_{}();", t)),
+                Stmt::Return(Cond::Always) => (false, "return;".to_owned()),
+                Stmt::Return(c) => (false, format!("if ({}) return;", self.cond(*c))),
+                Stmt::IndirectJump(Cond::Always, o) => {
+                    (false, format!("goto *{};", self.operand(*o)))
+                }
+                Stmt::IndirectJump(c, o) => (
+                    false,
+                    format!("if ({}) goto *{};", self.cond(*c), self.operand(*o)),
+                ),
+                Stmt::Goto(Cond::Always, t) => (false, format!("goto L{};", t)),
+                Stmt::Goto(c, t) => (false, format!("if ({}) goto L{};", self.cond(*c), t)),
+                Stmt::Halt => (false, "halt;".to_owned()),
+                Stmt::Invalid(raw) => (false, format!("invalid({});", raw)),
+                Stmt::If {
+                    cond,
+                    then,
+                    otherwise,
+                } => {
+                    writeln!(
+                        f,
+                        "{:>6}    {}if ({}) {{",
+                        line.addr,
+                        indent,
+                        self.cond(*cond)
+                    )?;
+                    self.write_lines(f, then, depth + 1, labels)?;
+                    if !otherwise.is_empty() {
+                        writeln!(f, "{:>6}    {}}} else {{", "", indent)?;
+                        self.write_lines(f, otherwise, depth + 1, labels)?;
+                    }
+                    writeln!(f, "{:>6}    {}}}", "", indent)?;
+                    continue;
+                }
+                Stmt::While { cond, body } => {
+                    if *cond == Cond::Always {
+                        writeln!(f, "{:>6}    {}loop {{", line.addr, indent)?;
+                    } else {
+                        writeln!(
+                            f,
+                            "{:>6}    {}while ({}) {{",
+                            line.addr,
+                            indent,
+                            self.cond(*cond)
+                        )?;
+                    }
+                    self.write_lines(f, body, depth + 1, labels)?;
+                    writeln!(f, "{:>6}    {}}}", "", indent)?;
+                    continue;
+                }
+                Stmt::DoWhile { body, cond } => {
+                    writeln!(f, "{:>6}    {}do {{", line.addr, indent)?;
+                    self.write_lines(f, body, depth + 1, labels)?;
+                    writeln!(f, "{:>6}    {}}} while ({});", "", indent, self.cond(*cond))?;
+                    continue;
+                }
+            };
+            let gutter = if io { "IO" } else { "  " };
+            writeln!(f, "{:>6}  {}{}{}", line.addr, gutter, indent, text)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut labels = HashSet::default();
+        Function::labels(&self.body, &mut labels);
+        writeln!(f, "fn fn_{}() {{", self.entry)?;
+        self.write_lines(f, &self.body, 1, &labels)?;
+        writeln!(f, "}}")
+    }
+}
+
+impl std::fmt::Display for Decompiled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, function) in self.functions.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", function)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_decompile_loop() {
+    // Counts down from the input, outputting each value
+    let data = [
+        3, 100, 1006, 100, 14, 4, 100, 1001, 100, -1, 100, 1105, 1, 2, 99,
+    ];
+    let res = decompile(&data);
+    assert_eq!(res.functions.len(), 1);
+
+    let body = &res.functions[0].body;
+    assert_eq!(body[0].stmt, Stmt::Input(Operand::Mem(100)));
+    if let Stmt::While { cond, body } = &body[1].stmt {
+        assert_eq!(*cond, Cond::NonZero(Operand::Mem(100)));
+        assert_eq!(body.len(), 2);
+    } else {
+        panic!("Expected a while loop, got {:?}", body[1]);
+    }
+    assert_eq!(body[2].stmt, Stmt::Halt);
+
+    let text = res.to_string();
+    assert!(text.contains("IO    mem[100] = input();"));
+    assert!(text.contains("while (mem[100] != 0) {"));
+}
+
+#[test]
+fn test_decompile_call() {
+    // main: push the return address, call `double(21)`, print and halt.
+    // double: set up a one-slot frame, double its argument, tear down and return.
+    let data = [
+        21101, 0, 11, 0, // ret = 11
+        21101, 0, 21, 1, // arg = 21
+        1105, 1, 14, // call 14
+        4, 200, // output(mem[200])
+        99,  // halt
+        109, 1, // rb += 1
+        21202, 0, 2, 1, // stack[2] = arg * 2
+        2101, 0, 1, 200, // mem[200] = stack[2]
+        109, -1, // rb -= 1
+        2106, 0, 0, // return
+    ];
+    let res = decompile(&data);
+    assert_eq!(res.functions.len(), 2);
+    assert_eq!(res.functions[0].body[2].stmt, Stmt::Call(14));
+
+    let text = res.to_string();
+    assert!(text.contains("call fn_14();"));
+    assert!(text.contains("IO    output(mem[200]);"));
+    assert!(text.contains("local0 = arg0 * 2;"));
+    assert!(text.contains("mem[200] = local0;"));
+    assert!(text.contains("return;"));
+}
+
+#[test]
+fn test_decompile_large_operands() {
+    const MAX: i64 = i64::MAX;
+    // rb += MAX; stack[MAX + 1] = MAX + MAX; jump to the halt.
+    let data = [109, MAX, 21101, MAX, MAX, 1, 1105, 1, 9, 99];
+    let res = decompile(&data);
+    assert_eq!(res.functions.len(), 1);
+    assert_eq!(res.functions[0].body.last().unwrap().stmt, Stmt::Halt);
+    res.to_string();
+
+    // rb += 1; rb += MAX; the relative base is no longer tracked.
+    let data = [109, 1, 109, MAX, 21101, 0, 0, 1, 99];
+    let res = decompile(&data);
+    assert!(res.to_string().contains("mem[rb + 1] = 0;"));
+}
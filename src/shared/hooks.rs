@@ -0,0 +1,187 @@
+use crate::shared::{Instruction, Program};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InstructionAction {
+    Execute,
+    /// Skip the instruction as if it were a no-op
+    Skip,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValueAction {
+    Keep,
+    /// Use this value instead
+    Replace(i64),
+    /// Drop an output, or block on an input as if none were queued
+    Veto,
+}
+
+/// Callbacks invoked by `Program::step_with` and `Program::run_with`.
+///
+/// Every method has a default that leaves execution unchanged, so implementors only need to
+/// override the events they care about.
+pub trait IntcodeHook {
+    /// Called before the instruction at `program.pc` executes.
+    fn before_instruction(&mut self, _program: &mut Program) -> InstructionAction {
+        InstructionAction::Execute
+    }
+
+    /// Called after the instruction that started at `pc` has executed.
+    fn after_instruction(&mut self, _program: &mut Program, _pc: usize) {}
+
+    /// Called when the program wants an input. `queued` is the next value from `inputs`, if any.
+    fn on_input(&mut self, _program: &mut Program, _queued: Option<i64>) -> ValueAction {
+        ValueAction::Keep
+    }
+
+    /// Called when the program outputs `value`.
+    fn on_output(&mut self, _program: &mut Program, _value: i64) -> ValueAction {
+        ValueAction::Keep
+    }
+}
+
+impl IntcodeHook for () {}
+
+impl<H: IntcodeHook> IntcodeHook for &mut H {
+    fn before_instruction(&mut self, program: &mut Program) -> InstructionAction {
+        (**self).before_instruction(program)
+    }
+
+    fn after_instruction(&mut self, program: &mut Program, pc: usize) {
+        (**self).after_instruction(program, pc)
+    }
+
+    fn on_input(&mut self, program: &mut Program, queued: Option<i64>) -> ValueAction {
+        (**self).on_input(program, queued)
+    }
+
+    fn on_output(&mut self, program: &mut Program, value: i64) -> ValueAction {
+        (**self).on_output(program, value)
+    }
+}
+
+/// Runs two hooks in sequence. Vetoes and replacements from the first hook win.
+impl<A: IntcodeHook, B: IntcodeHook> IntcodeHook for (A, B) {
+    fn before_instruction(&mut self, program: &mut Program) -> InstructionAction {
+        match self.0.before_instruction(program) {
+            InstructionAction::Execute => self.1.before_instruction(program),
+            skip => skip,
+        }
+    }
+
+    fn after_instruction(&mut self, program: &mut Program, pc: usize) {
+        self.0.after_instruction(program, pc);
+        self.1.after_instruction(program, pc);
+    }
+
+    fn on_input(&mut self, program: &mut Program, queued: Option<i64>) -> ValueAction {
+        match self.0.on_input(program, queued) {
+            ValueAction::Keep => self.1.on_input(program, queued),
+            action => action,
+        }
+    }
+
+    fn on_output(&mut self, program: &mut Program, value: i64) -> ValueAction {
+        match self.0.on_output(program, value) {
+            ValueAction::Keep => self.1.on_output(program, value),
+            action => action,
+        }
+    }
+}
+
+/// Records every instruction executed, along with its address.
+#[derive(Clone, Debug, Default)]
+pub struct Tracer {
+    pub trace: Vec<(usize, Instruction)>,
+}
+
+impl IntcodeHook for Tracer {
+    fn before_instruction(&mut self, program: &mut Program) -> InstructionAction {
+        self.trace.push((program.pc, program.instruction()));
+        InstructionAction::Execute
+    }
+}
+
+/// Counts how many times each address is executed.
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    pub counts: std::collections::HashMap<usize, usize>,
+}
+
+impl IntcodeHook for Profiler {
+    fn after_instruction(&mut self, _program: &mut Program, pc: usize) {
+        *self.counts.entry(pc).or_insert(0) += 1;
+    }
+}
+
+#[test]
+fn test_hooks() {
+    // Reads a value, doubles it, outputs it and loops back to the start
+    let data = [3, 13, 1002, 13, 2, 13, 4, 13, 1105, 1, 0, 99, 0, 0];
+
+    struct Controller;
+    impl IntcodeHook for Controller {
+        fn on_input(&mut self, _program: &mut Program, queued: Option<i64>) -> ValueAction {
+            ValueAction::Replace(queued.unwrap_or(10) + 1)
+        }
+        fn on_output(&mut self, program: &mut Program, _value: i64) -> ValueAction {
+            if program.outputs.len() >= 2 {
+                ValueAction::Veto
+            } else {
+                ValueAction::Keep
+            }
+        }
+    }
+
+    let mut program = Program::new(&data, &[5]);
+    let mut tracer = Tracer::default();
+    for _ in 0..20 {
+        program.step_with(&mut (Controller, &mut tracer));
+    }
+    assert_eq!(program.outputs, vec![12, 22]);
+    assert_eq!(tracer.trace.len(), 20);
+    assert_eq!(tracer.trace[0], (0, Instruction::decode(3).unwrap()));
+
+    struct SkipMultiply;
+    impl IntcodeHook for SkipMultiply {
+        fn before_instruction(&mut self, program: &mut Program) -> InstructionAction {
+            if program.instruction().opcode == crate::shared::Opcodes::Multiplication {
+                InstructionAction::Skip
+            } else {
+                InstructionAction::Execute
+            }
+        }
+    }
+
+    let mut program = Program::new(&data, &[5, 7]);
+    let mut profiler = Profiler::default();
+    program.run_with(&mut (SkipMultiply, &mut profiler));
+    assert_eq!(program.outputs, vec![5, 7]);
+    assert_eq!(
+        program.get_status(),
+        crate::shared::IntcodeStepResult::WaitingForInput
+    );
+    assert_eq!(profiler.counts[&6], 2);
+}
+
+#[test]
+fn test_hook_patches_instruction() {
+    let data = [3, 13, 1002, 13, 2, 13, 4, 13, 1105, 1, 0, 99, 0, 0];
+
+    // Turns the multiply into an add just before it runs, so the patched instruction is the
+    // one executed.
+    struct MultiplyToAdd;
+    impl IntcodeHook for MultiplyToAdd {
+        fn before_instruction(&mut self, program: &mut Program) -> InstructionAction {
+            if program[program.pc] == 1002 {
+                let pc = program.pc;
+                program[pc] = 1001;
+            }
+            InstructionAction::Execute
+        }
+    }
+
+    let mut program = Program::new(&data, &[5]);
+    program.run_with(&mut MultiplyToAdd);
+    assert_eq!(program.outputs, vec![7]);
+}
@@ -1,6 +1,107 @@
 use num_enum::TryFromPrimitive;
-use std::collections::HashMap;
 use std::convert::TryInto;
+use std::sync::Arc;
+
+use crate::shared::{InstructionAction, IntcodeHook, Memory, ValueAction};
+
+/// An immutable, cheaply cloneable Intcode program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramImage(Arc<[i64]>);
+
+impl ProgramImage {
+    pub fn new(data: &[i64]) -> Self {
+        ProgramImage(data.into())
+    }
+}
+
+impl std::ops::Deref for ProgramImage {
+    type Target = [i64];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<i64>> for ProgramImage {
+    fn from(data: Vec<i64>) -> Self {
+        ProgramImage(data.into())
+    }
+}
+
+impl std::str::FromStr for ProgramImage {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_program(s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Index of the offending value within the program
+    pub index: usize,
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid Intcode value {:?} at line {}, column {} (position {})",
+            self.token, self.line, self.column, self.index
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses comma-separated Intcode. Whitespace and newlines around values are ignored, `#` starts
+/// a comment running to the end of the line, and a trailing comma is allowed.
+pub fn parse_program(input: &str) -> Result<ProgramImage, ParseError> {
+    // Split into comma-separated tokens, remembering where each one starts
+    let mut tokens: Vec<(String, usize, usize)> = vec![(String::new(), 1, 1)];
+    for (line_idx, line) in input.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        let token = &mut tokens.last_mut().unwrap().0;
+        if !token.is_empty() {
+            // Values on different lines must still be separated by a comma
+            token.push(' ');
+        }
+        for (col_idx, ch) in line.chars().enumerate() {
+            let (token, line, column) = tokens.last_mut().unwrap();
+            if ch == ',' {
+                tokens.push((String::new(), line_idx + 1, col_idx + 2));
+            } else if !ch.is_whitespace() || !token.trim().is_empty() {
+                if token.is_empty() {
+                    *line = line_idx + 1;
+                    *column = col_idx + 1;
+                }
+                token.push(ch);
+            }
+        }
+    }
+
+    let count = tokens.len();
+    let mut data = Vec::with_capacity(count);
+    for (index, (token, line, column)) in tokens.into_iter().enumerate() {
+        let token = token.trim_end();
+        if token.is_empty() && index + 1 == count {
+            // Trailing comma or empty input
+            break;
+        }
+        let val = token.parse().map_err(|_| ParseError {
+            index,
+            line,
+            column,
+            token: token.to_owned(),
+        })?;
+        data.push(val);
+    }
+
+    Ok(ProgramImage::from(data))
+}
 
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, TryFromPrimitive)]
@@ -29,11 +130,66 @@ fn get_num(digits: &[u8]) -> u8 {
     let mut acc = 0u8;
     for d in digits {
         acc *= 10;
-        acc += *d as u8;
+        acc += *d;
     }
     acc
 }
 
+impl Opcodes {
+    /// Number of parameters the instruction takes.
+    pub fn param_count(self) -> usize {
+        match self {
+            Opcodes::Addition | Opcodes::Multiplication | Opcodes::LessThan | Opcodes::Equals => 3,
+            Opcodes::JumpIfTrue | Opcodes::JumpIfFalse => 2,
+            Opcodes::Input | Opcodes::Output | Opcodes::RelativeBaseOffset => 1,
+            Opcodes::Halt => 0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: Opcodes,
+    pub modes: [ParameterModes; 3],
+}
+
+impl Instruction {
+    /// Decodes an instruction value, or returns `None` if the opcode or a mode is unknown.
+    pub fn decode(mut instruction: i64) -> Option<Self> {
+        if !(0..100_000).contains(&instruction) {
+            return None;
+        }
+        let digits = {
+            let mut digits = [0; 5];
+            for digit in digits.iter_mut().rev() {
+                *digit = (instruction % 10) as u8;
+                instruction /= 10;
+            }
+            digits
+        };
+
+        // ABCDE
+        // DE = two-digit opcode
+        // C  = mode of 1st parameter
+        // B  = mode of 2nd parameter
+        // A  = mode of 3rd parameter
+
+        Some(Instruction {
+            opcode: get_num(&digits[3..5]).try_into().ok()?,
+            modes: [
+                digits[2].try_into().ok()?,
+                digits[1].try_into().ok()?,
+                digits[0].try_into().ok()?,
+            ],
+        })
+    }
+
+    /// Length of the instruction including its parameters.
+    pub fn size(&self) -> usize {
+        1 + self.opcode.param_count()
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum IntcodeStepResult {
     Ok,
@@ -41,9 +197,9 @@ pub enum IntcodeStepResult {
     WaitingForInput,
 }
 
+#[derive(Clone)]
 pub struct Program {
-    pub data: Vec<i64>,
-    pub memory: HashMap<usize, i64>,
+    pub memory: Memory,
     pub pc: usize,
     pub inputs: Vec<i64>,
     pub outputs: Vec<i64>,
@@ -54,9 +210,14 @@ pub struct Program {
 
 impl Program {
     pub fn new(data: &[i64], inputs: &[i64]) -> Self {
+        Program::from_image(&ProgramImage::new(data), inputs)
+    }
+
+    /// Creates a machine backed by a shared image. Memory is only copied, a page at a time,
+    /// once the machine writes to it.
+    pub fn from_image(image: &ProgramImage, inputs: &[i64]) -> Self {
         Program {
-            data: data.to_vec(),
-            memory: HashMap::default(),
+            memory: Memory::new(image),
             pc: 0,
             inputs: inputs.to_vec(),
             outputs: vec![],
@@ -83,33 +244,33 @@ impl Program {
         }
     }
 
+    /// Decodes the instruction at the program counter.
+    pub fn instruction(&self) -> Instruction {
+        Instruction::decode(self[self.pc])
+            .unwrap_or_else(|| panic!("Invalid instruction {} at {}", self[self.pc], self.pc))
+    }
+
     pub fn step(&mut self) -> IntcodeStepResult {
+        self.step_with(&mut ())
+    }
+
+    /// Executes one instruction, giving `hook` the chance to observe or change it.
+    pub fn step_with<H: IntcodeHook>(&mut self, hook: &mut H) -> IntcodeStepResult {
         if self.status == IntcodeStepResult::Halt {
             return self.status;
         }
 
-        let mut instruction = self[self.pc];
-        let digits = {
-            let mut digits = [0; 5];
-            let mut index = 4;
-            while instruction > 0 {
-                digits[index] = (instruction % 10) as u8;
-                instruction /= 10;
-                index -= 1;
-            }
-            digits
-        };
-
-        // ABCDE
-        // DE = two-digit opcode
-        // C  = mode of 1st parameter
-        // B  = mode of 2nd parameter
-        // A  = mode of 3rd parameter
-
-        let opcode: Opcodes = get_num(&digits[3..5]).try_into().unwrap();
-        let param1_mode: ParameterModes = digits[2].try_into().unwrap();
-        let param2_mode: ParameterModes = digits[1].try_into().unwrap();
-        let param3_mode: ParameterModes = digits[0].try_into().unwrap();
+        let action = hook.before_instruction(self);
+        // Decode only now, so any change the hook made to memory or `pc` takes effect.
+        let instruction = self.instruction();
+        if action == InstructionAction::Skip {
+            self.pc += instruction.size();
+            self.status = IntcodeStepResult::Ok;
+            return self.status;
+        }
+        let Instruction { opcode, modes } = instruction;
+        let [param1_mode, param2_mode, param3_mode] = modes;
+        let pc = self.pc;
 
         match opcode {
             Opcodes::Addition => {
@@ -127,19 +288,28 @@ impl Program {
                 self.pc += 4;
             }
             Opcodes::Input => {
-                if self.input_idx >= self.inputs.len() {
-                    self.status = IntcodeStepResult::WaitingForInput;
-                    return self.status;
+                let queued = self.inputs.get(self.input_idx).copied();
+                let input = match (hook.on_input(self, queued), queued) {
+                    (ValueAction::Keep, Some(input)) | (ValueAction::Replace(input), _) => input,
+                    (ValueAction::Keep, None) | (ValueAction::Veto, _) => {
+                        self.status = IntcodeStepResult::WaitingForInput;
+                        return self.status;
+                    }
+                };
+                if queued.is_some() {
+                    self.input_idx += 1;
                 }
-                let input = self.inputs[self.input_idx];
-                self.input_idx += 1;
                 let out = self.get_val_mut(self[self.pc + 1], param1_mode);
                 *out = input;
                 self.pc += 2;
             }
             Opcodes::Output => {
                 let in1 = self.get_val(self[self.pc + 1], param1_mode);
-                self.outputs.push(in1);
+                match hook.on_output(self, in1) {
+                    ValueAction::Keep => self.outputs.push(in1),
+                    ValueAction::Replace(v) => self.outputs.push(v),
+                    ValueAction::Veto => (),
+                }
                 self.pc += 2;
             }
             Opcodes::JumpIfTrue => {
@@ -181,10 +351,12 @@ impl Program {
             }
             Opcodes::Halt => {
                 self.status = IntcodeStepResult::Halt;
+                hook.after_instruction(self, pc);
                 return self.status;
             }
         }
         self.status = IntcodeStepResult::Ok;
+        hook.after_instruction(self, pc);
         self.status
     }
 
@@ -192,6 +364,15 @@ impl Program {
         while self.step() == IntcodeStepResult::Ok {}
     }
 
+    /// Runs until the program halts or blocks on input, calling `hook` around every instruction.
+    pub fn run_with<H: IntcodeHook>(&mut self, hook: &mut H) {
+        while self.step_with(hook) == IntcodeStepResult::Ok {}
+    }
+
+    pub fn relative_base(&self) -> usize {
+        self.relative_base
+    }
+
     pub fn add_input(&mut self, input: i64) {
         self.inputs.push(input);
     }
@@ -205,23 +386,38 @@ impl std::ops::Index<usize> for Program {
     type Output = i64;
 
     fn index(&self, idx: usize) -> &Self::Output {
-        if idx < self.data.len() {
-            &self.data[idx]
-        } else if self.memory.contains_key(&idx) {
-            self.memory.get(&idx).unwrap()
-        } else {
-            &0
-        }
+        &self.memory[idx]
     }
 }
 
 impl std::ops::IndexMut<usize> for Program {
     fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
-        if idx < self.data.len() {
-            &mut self.data[idx]
-        } else {
-            self.memory.entry(idx).or_insert(0);
-            self.memory.get_mut(&idx).unwrap()
-        }
+        &mut self.memory[idx]
     }
 }
+
+#[test]
+fn test_parse_program() {
+    let image = parse_program("1,9,10,3,\n2,3,11,0, # multiply\n99,30,40,50\n").unwrap();
+    assert_eq!(&image[..], &[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]);
+
+    let shared = image.clone();
+    let mut program = Program::new(&shared, &[]);
+    program.run();
+    assert_eq!(program[0], 3500);
+    assert_eq!(image[0], 1);
+}
+
+#[test]
+fn test_parse_program_errors() {
+    let err = parse_program("1,2,\n3,x4,99").unwrap_err();
+    assert_eq!(err.index, 3);
+    assert_eq!((err.line, err.column), (2, 3));
+    assert_eq!(err.token, "x4");
+
+    let err = parse_program("1,,2").unwrap_err();
+    assert_eq!((err.index, err.line, err.column), (1, 1, 3));
+
+    let err = parse_program("1,2\n3").unwrap_err();
+    assert_eq!(err.token, "2 3");
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::shared::ProgramImage;
+
+pub const PAGE_SIZE: usize = 64;
+
+/// Pages past this index are kept in a map, so a stray write to a huge address doesn't
+/// allocate a huge page table.
+const MAX_DENSE_PAGES: usize = 1 << 16;
+
+type Page = [i64; PAGE_SIZE];
+
+/// Copy-on-write Intcode memory.
+///
+/// Reads fall through to the shared program image until a page is written to; only then is
+/// that page copied. Cloning a `Memory` shares every page it has materialised so far, so forked
+/// machines only pay for the pages they go on to change.
+#[derive(Clone, Debug)]
+pub struct Memory {
+    image: ProgramImage,
+    pages: Vec<Option<Arc<Page>>>,
+    far_pages: HashMap<usize, Arc<Page>>,
+}
+
+impl Memory {
+    pub fn new(image: &ProgramImage) -> Self {
+        Memory {
+            image: image.clone(),
+            pages: vec![],
+            far_pages: HashMap::default(),
+        }
+    }
+
+    pub fn image(&self) -> &ProgramImage {
+        &self.image
+    }
+
+    /// Number of pages this memory owns a copy of.
+    pub fn materialized_pages(&self) -> usize {
+        self.pages.iter().filter(|p| p.is_some()).count() + self.far_pages.len()
+    }
+
+    fn page(&self, page: usize) -> Option<&Page> {
+        if page < MAX_DENSE_PAGES {
+            self.pages.get(page).and_then(|p| p.as_deref())
+        } else {
+            self.far_pages.get(&page).map(|p| &**p)
+        }
+    }
+
+    fn page_mut(&mut self, page: usize) -> &mut Page {
+        let image = &self.image;
+        let fresh = || {
+            let mut p = [0; PAGE_SIZE];
+            let start = std::cmp::min(page * PAGE_SIZE, image.len());
+            let end = std::cmp::min(start + PAGE_SIZE, image.len());
+            p[..end - start].copy_from_slice(&image[start..end]);
+            Arc::new(p)
+        };
+        let slot = if page < MAX_DENSE_PAGES {
+            if self.pages.len() <= page {
+                self.pages.resize(page + 1, None);
+            }
+            self.pages[page].get_or_insert_with(fresh)
+        } else {
+            self.far_pages.entry(page).or_insert_with(fresh)
+        };
+        Arc::make_mut(slot)
+    }
+}
+
+impl std::ops::Index<usize> for Memory {
+    type Output = i64;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        match self.page(idx / PAGE_SIZE) {
+            Some(p) => &p[idx % PAGE_SIZE],
+            None => self.image.get(idx).unwrap_or(&0),
+        }
+    }
+}
+
+impl std::ops::IndexMut<usize> for Memory {
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        &mut self.page_mut(idx / PAGE_SIZE)[idx % PAGE_SIZE]
+    }
+}
+
+#[test]
+fn test_memory_copy_on_write() {
+    let image = ProgramImage::from((0..200).collect::<Vec<i64>>());
+    let mut a = Memory::new(&image);
+    assert_eq!(a[150], 150);
+    assert_eq!(a[10_000], 0);
+    assert_eq!(a.materialized_pages(), 0);
+
+    a[130] = -1;
+    a[1_000_000_000] = 7;
+    assert_eq!(a.materialized_pages(), 2);
+    assert_eq!((a[129], a[130], a[131]), (129, -1, 131));
+    assert_eq!(a[1_000_000_000], 7);
+
+    let mut b = a.clone();
+    b[130] = -2;
+    assert_eq!((a[130], b[130]), (-1, -2));
+    assert_eq!(image[130], 130);
+}
@@ -1,3 +1,11 @@
+mod decompile;
+mod hooks;
 mod intcode;
+mod memory;
+mod symbolic;
 
+pub use decompile::*;
+pub use hooks::*;
 pub use intcode::*;
+pub use memory::*;
+pub use symbolic::*;
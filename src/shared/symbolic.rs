@@ -0,0 +1,736 @@
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+use std::ops::RangeInclusive;
+
+use crate::shared::{IntcodeStepResult, Opcodes, ParameterModes, Program, ProgramImage};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(pub usize);
+
+/// A value computed by the symbolic VM.
+///
+/// `Load` is a read through a symbolic address. It remembers how many writes had happened at
+/// that point, so it can be resolved against the memory state of that moment.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Expr {
+    Const(i64),
+    Sym(Symbol),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    LessThan(Box<Expr>, Box<Expr>),
+    Equals(Box<Expr>, Box<Expr>),
+    Load { addr: Box<Expr>, version: usize },
+}
+
+impl Expr {
+    pub fn as_const(&self) -> Option<i64> {
+        if let Expr::Const(c) = self {
+            Some(*c)
+        } else {
+            None
+        }
+    }
+
+    /// Adds two expressions, folding constants. Returns `None` if the constants overflow.
+    fn add(a: Expr, b: Expr) -> Option<Expr> {
+        Some(match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const(a.checked_add(b)?),
+            (Expr::Const(0), e) | (e, Expr::Const(0)) => e,
+            (a, b) => Expr::Add(Box::new(a), Box::new(b)),
+        })
+    }
+
+    /// Multiplies two expressions, folding constants. Returns `None` if the constants overflow.
+    fn mul(a: Expr, b: Expr) -> Option<Expr> {
+        Some(match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const(a.checked_mul(b)?),
+            (Expr::Const(0), _) | (_, Expr::Const(0)) => Expr::Const(0),
+            (Expr::Const(1), e) | (e, Expr::Const(1)) => e,
+            (a, b) => Expr::Mul(Box::new(a), Box::new(b)),
+        })
+    }
+
+    fn less_than(a: Expr, b: Expr) -> Expr {
+        match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const(if a < b { 1 } else { 0 }),
+            (a, b) => Expr::LessThan(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn equals(a: Expr, b: Expr) -> Expr {
+        match (a, b) {
+            (Expr::Const(a), Expr::Const(b)) => Expr::Const(if a == b { 1 } else { 0 }),
+            (a, b) if a == b => Expr::Const(1),
+            (a, b) => Expr::Equals(Box::new(a), Box::new(b)),
+        }
+    }
+
+    /// Converts the expression to a polynomial over its symbols, if it is one and its
+    /// coefficients fit in an `i64`.
+    pub fn to_poly(&self) -> Option<Poly> {
+        match self {
+            Expr::Const(c) => Some(Poly::constant(*c)),
+            Expr::Sym(s) => Some(Poly::symbol(*s)),
+            Expr::Add(a, b) => a.to_poly()?.add(&b.to_poly()?),
+            Expr::Mul(a, b) => a.to_poly()?.mul(&b.to_poly()?),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Const(c) => write!(f, "{}", c),
+            Expr::Sym(s) => write!(f, "s{}", s.0),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Mul(a, b) => write!(f, "({} * {})", a, b),
+            Expr::LessThan(a, b) => write!(f, "({} < {})", a, b),
+            Expr::Equals(a, b) => write!(f, "({} == {})", a, b),
+            Expr::Load { addr, version } => write!(f, "mem@{}[{}]", version, addr),
+        }
+    }
+}
+
+/// A polynomial with integer coefficients. Each monomial is a sorted list of symbols, so
+/// `s0 * s0 * s1` is stored as `[s0, s0, s1]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Poly {
+    pub terms: BTreeMap<Vec<Symbol>, i64>,
+}
+
+impl Poly {
+    pub fn constant(c: i64) -> Self {
+        let mut terms = BTreeMap::new();
+        if c != 0 {
+            terms.insert(vec![], c);
+        }
+        Poly { terms }
+    }
+
+    pub fn symbol(s: Symbol) -> Self {
+        let mut terms = BTreeMap::new();
+        terms.insert(vec![s], 1);
+        Poly { terms }
+    }
+
+    /// Adds `coefficient` to a term. Returns `None` if the coefficient overflows.
+    fn insert(&mut self, monomial: Vec<Symbol>, coefficient: i64) -> Option<()> {
+        let c = self.terms.entry(monomial.clone()).or_insert(0);
+        *c = c.checked_add(coefficient)?;
+        if *c == 0 {
+            self.terms.remove(&monomial);
+        }
+        Some(())
+    }
+
+    /// The sum, or `None` if a coefficient overflows.
+    pub fn add(&self, other: &Poly) -> Option<Poly> {
+        let mut res = self.clone();
+        for (m, &c) in other.terms.iter() {
+            res.insert(m.clone(), c)?;
+        }
+        Some(res)
+    }
+
+    /// The product, or `None` if a coefficient overflows.
+    pub fn mul(&self, other: &Poly) -> Option<Poly> {
+        let mut res = Poly::default();
+        for (m1, &c1) in self.terms.iter() {
+            for (m2, &c2) in other.terms.iter() {
+                let mut m = m1.iter().chain(m2.iter()).copied().collect_vec();
+                m.sort();
+                res.insert(m, c1.checked_mul(c2)?)?;
+            }
+        }
+        Some(res)
+    }
+
+    pub fn degree_in(&self, s: Symbol) -> usize {
+        self.terms
+            .keys()
+            .map(|m| m.iter().filter(|&&x| x == s).count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The value with the given symbol values, or `None` if it overflows.
+    pub fn eval(&self, values: &HashMap<Symbol, i64>) -> Option<i64> {
+        self.terms.iter().try_fold(0i64, |sum, (m, &c)| {
+            let term = m.iter().try_fold(c, |acc, s| acc.checked_mul(values[s]))?;
+            sum.checked_add(term)
+        })
+    }
+
+    /// Splits the polynomial into `s * p + q`, assuming `s` has degree at most 1.
+    fn split_linear(&self, s: Symbol) -> (Poly, Poly) {
+        let mut p = Poly::default();
+        let mut q = Poly::default();
+        for (m, &c) in self.terms.iter() {
+            if let Some(pos) = m.iter().position(|&x| x == s) {
+                let mut rest = m.clone();
+                rest.remove(pos);
+                // Each monomial only appears once, so nothing is summed and nothing overflows.
+                let _ = p.insert(rest, c);
+            } else {
+                let _ = q.insert(m.clone(), c);
+            }
+        }
+        (p, q)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SymbolicError {
+    /// A jump condition or target depends on a symbol.
+    SymbolicBranch {
+        pc: usize,
+    },
+    /// An instruction would write through a symbolic address.
+    SymbolicWrite {
+        pc: usize,
+    },
+    /// The relative base would become symbolic.
+    SymbolicRelativeBase {
+        pc: usize,
+    },
+    /// The opcode or parameter modes at `pc` depend on a symbol.
+    SymbolicInstruction {
+        pc: usize,
+    },
+    InvalidInstruction {
+        pc: usize,
+    },
+    WaitingForInput {
+        pc: usize,
+    },
+    /// Constant folding at `pc` overflowed an `i64`.
+    Overflow {
+        pc: usize,
+    },
+    /// The program was still running after the step limit.
+    StepLimit {
+        pc: usize,
+    },
+}
+
+impl std::fmt::Display for SymbolicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymbolicError::SymbolicBranch { pc } => write!(f, "symbolic branch at {}", pc),
+            SymbolicError::SymbolicWrite { pc } => write!(f, "symbolic write address at {}", pc),
+            SymbolicError::SymbolicRelativeBase { pc } => {
+                write!(f, "symbolic relative base at {}", pc)
+            }
+            SymbolicError::SymbolicInstruction { pc } => {
+                write!(f, "symbolic instruction at {}", pc)
+            }
+            SymbolicError::InvalidInstruction { pc } => write!(f, "invalid instruction at {}", pc),
+            SymbolicError::WaitingForInput { pc } => write!(f, "ran out of input at {}", pc),
+            SymbolicError::Overflow { pc } => write!(f, "arithmetic overflow at {}", pc),
+            SymbolicError::StepLimit { pc } => write!(f, "step limit reached at {}", pc),
+        }
+    }
+}
+
+impl std::error::Error for SymbolicError {}
+
+/// How many instructions `SymbolicProgram::run` and the concrete fallback of `solve_for`
+/// execute before giving up on a program that doesn't halt.
+pub const DEFAULT_STEP_LIMIT: usize = 1_000_000;
+
+/// An Intcode machine whose memory cells and inputs may hold symbolic expressions.
+pub struct SymbolicProgram {
+    initial: Vec<Expr>,
+    memory: HashMap<usize, Expr>,
+    writes: Vec<(usize, Expr)>,
+    pub pc: usize,
+    pub inputs: Vec<Expr>,
+    pub outputs: Vec<Expr>,
+    input_idx: usize,
+    relative_base: i64,
+    symbols: usize,
+    steps: usize,
+    step_limit: usize,
+}
+
+impl SymbolicProgram {
+    pub fn new(data: &[i64], inputs: &[i64]) -> Self {
+        SymbolicProgram {
+            initial: data.iter().map(|&x| Expr::Const(x)).collect(),
+            memory: HashMap::default(),
+            writes: vec![],
+            pc: 0,
+            inputs: inputs.iter().map(|&x| Expr::Const(x)).collect(),
+            outputs: vec![],
+            input_idx: 0,
+            relative_base: 0,
+            symbols: 0,
+            steps: 0,
+            step_limit: DEFAULT_STEP_LIMIT,
+        }
+    }
+
+    /// The number of instructions `run` executes before failing with `StepLimit`.
+    pub fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    pub fn new_symbol(&mut self) -> Symbol {
+        self.symbols += 1;
+        Symbol(self.symbols - 1)
+    }
+
+    /// Replaces the initial contents of `addr` with a fresh symbol.
+    pub fn symbolize_cell(&mut self, addr: usize) -> Symbol {
+        let s = self.new_symbol();
+        if addr >= self.initial.len() {
+            self.initial.resize(addr + 1, Expr::Const(0));
+        }
+        self.initial[addr] = Expr::Sym(s);
+        s
+    }
+
+    /// Replaces the input at `idx` with a fresh symbol.
+    pub fn symbolize_input(&mut self, idx: usize) -> Symbol {
+        let s = self.new_symbol();
+        if idx >= self.inputs.len() {
+            self.inputs.resize(idx + 1, Expr::Const(0));
+        }
+        self.inputs[idx] = Expr::Sym(s);
+        s
+    }
+
+    pub fn get(&self, addr: usize) -> Expr {
+        self.memory
+            .get(&addr)
+            .or_else(|| self.initial.get(addr))
+            .cloned()
+            .unwrap_or(Expr::Const(0))
+    }
+
+    fn set(&mut self, addr: usize, val: Expr) {
+        self.writes.push((addr, val.clone()));
+        self.memory.insert(addr, val);
+    }
+
+    fn read(&self, param: Expr, mode: ParameterModes) -> Result<Expr, SymbolicError> {
+        let addr = match mode {
+            ParameterModes::Immediate => return Ok(param),
+            ParameterModes::Position => param,
+            ParameterModes::Relative => Expr::add(Expr::Const(self.relative_base), param)
+                .ok_or(SymbolicError::Overflow { pc: self.pc })?,
+        };
+        Ok(match addr.as_const() {
+            Some(a) => self.get(a as usize),
+            None => Expr::Load {
+                addr: Box::new(addr),
+                version: self.writes.len(),
+            },
+        })
+    }
+
+    fn write_addr(&self, param: Expr, mode: ParameterModes) -> Result<usize, SymbolicError> {
+        let pc = self.pc;
+        let addr = match mode {
+            ParameterModes::Immediate => return Err(SymbolicError::InvalidInstruction { pc }),
+            ParameterModes::Position => param.as_const(),
+            ParameterModes::Relative => match param.as_const() {
+                Some(a) => Some(
+                    a.checked_add(self.relative_base)
+                        .ok_or(SymbolicError::Overflow { pc })?,
+                ),
+                None => None,
+            },
+        };
+        addr.map(|a| a as usize)
+            .ok_or(SymbolicError::SymbolicWrite { pc })
+    }
+
+    /// Executes a single instruction. Returns `Ok(IntcodeStepResult::Halt)` once halted.
+    pub fn step(&mut self) -> Result<IntcodeStepResult, SymbolicError> {
+        let pc = self.pc;
+        let instruction = self
+            .get(pc)
+            .as_const()
+            .ok_or(SymbolicError::SymbolicInstruction { pc })?;
+
+        let invalid = SymbolicError::InvalidInstruction { pc };
+        let opcode: Opcodes = ((instruction % 100) as u8)
+            .try_into()
+            .map_err(|_| invalid)?;
+        let mode = |n: u32| -> Result<ParameterModes, SymbolicError> {
+            ((instruction / 10i64.pow(n + 1) % 10) as u8)
+                .try_into()
+                .map_err(|_| invalid)
+        };
+        let (m1, m2, m3) = (mode(1)?, mode(2)?, mode(3)?);
+        let p1 = self.get(pc + 1);
+        let p2 = self.get(pc + 2);
+        let p3 = self.get(pc + 3);
+
+        match opcode {
+            Opcodes::Addition | Opcodes::Multiplication | Opcodes::LessThan | Opcodes::Equals => {
+                let in1 = self.read(p1, m1)?;
+                let in2 = self.read(p2, m2)?;
+                let out = self.write_addr(p3, m3)?;
+                let val = match opcode {
+                    Opcodes::Addition => Expr::add(in1, in2),
+                    Opcodes::Multiplication => Expr::mul(in1, in2),
+                    Opcodes::LessThan => Some(Expr::less_than(in1, in2)),
+                    _ => Some(Expr::equals(in1, in2)),
+                }
+                .ok_or(SymbolicError::Overflow { pc })?;
+                self.set(out, val);
+                self.pc += 4;
+            }
+            Opcodes::Input => {
+                if self.input_idx >= self.inputs.len() {
+                    return Err(SymbolicError::WaitingForInput { pc });
+                }
+                let out = self.write_addr(p1, m1)?;
+                let input = self.inputs[self.input_idx].clone();
+                self.input_idx += 1;
+                self.set(out, input);
+                self.pc += 2;
+            }
+            Opcodes::Output => {
+                let val = self.read(p1, m1)?;
+                self.outputs.push(val);
+                self.pc += 2;
+            }
+            Opcodes::JumpIfTrue | Opcodes::JumpIfFalse => {
+                let cond = self.read(p1, m1)?.as_const();
+                let target = self.read(p2, m2)?.as_const();
+                let cond = cond.ok_or(SymbolicError::SymbolicBranch { pc })?;
+                if (cond != 0) == (opcode == Opcodes::JumpIfTrue) {
+                    self.pc = target.ok_or(SymbolicError::SymbolicBranch { pc })? as usize;
+                } else {
+                    self.pc += 3;
+                }
+            }
+            Opcodes::RelativeBaseOffset => {
+                let offset = self
+                    .read(p1, m1)?
+                    .as_const()
+                    .ok_or(SymbolicError::SymbolicRelativeBase { pc })?;
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(offset)
+                    .ok_or(SymbolicError::Overflow { pc })?;
+                self.pc += 2;
+            }
+            Opcodes::Halt => return Ok(IntcodeStepResult::Halt),
+        }
+        Ok(IntcodeStepResult::Ok)
+    }
+
+    /// Runs until the program halts, failing with `StepLimit` if it is still running after
+    /// the step limit.
+    pub fn run(&mut self) -> Result<(), SymbolicError> {
+        while self.step()? == IntcodeStepResult::Ok {
+            self.steps += 1;
+            if self.steps >= self.step_limit {
+                return Err(SymbolicError::StepLimit { pc: self.pc });
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates `expr` with the given symbol values, resolving any symbolic loads against the
+    /// writes this machine performed. Returns `None` if the arithmetic overflows.
+    pub fn eval(&self, expr: &Expr, values: &HashMap<Symbol, i64>) -> Option<i64> {
+        Some(match expr {
+            Expr::Const(c) => *c,
+            Expr::Sym(s) => values[s],
+            Expr::Add(a, b) => self.eval(a, values)?.checked_add(self.eval(b, values)?)?,
+            Expr::Mul(a, b) => self.eval(a, values)?.checked_mul(self.eval(b, values)?)?,
+            Expr::LessThan(a, b) => (self.eval(a, values)? < self.eval(b, values)?) as i64,
+            Expr::Equals(a, b) => (self.eval(a, values)? == self.eval(b, values)?) as i64,
+            Expr::Load { addr, version } => {
+                let addr = self.eval(addr, values)? as usize;
+                let val = self.writes[..*version]
+                    .iter()
+                    .rev()
+                    .find(|(a, _)| *a == addr)
+                    .map(|(_, v)| v)
+                    .or_else(|| self.initial.get(addr));
+                match val {
+                    Some(v) => self.eval(v, values)?,
+                    None => 0,
+                }
+            }
+        })
+    }
+}
+
+/// A cell whose value is unknown.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Unknown {
+    Memory(usize),
+    Input(usize),
+}
+
+/// The value we want to constrain once the program halts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Memory(usize),
+    Output(usize),
+}
+
+/// Finds values for `unknowns`, each taken from its range, such that `target` equals `value`
+/// once the program halts.
+///
+/// The program is executed symbolically first. If the target is a polynomial, we solve for any
+/// unknown that appears linearly and only enumerate the others; otherwise the resulting
+/// expression is evaluated over every combination. If the program branches on an unknown or the
+/// arithmetic overflows, we fall back to running the VM concretely for each combination.
+///
+/// Returns `None` if no combination works, or if the program doesn't halt within
+/// `DEFAULT_STEP_LIMIT` steps.
+pub fn solve_for(
+    data: &[i64],
+    inputs: &[i64],
+    unknowns: &[(Unknown, RangeInclusive<i64>)],
+    target: Target,
+    value: i64,
+) -> Option<Vec<i64>> {
+    match solve_symbolic(data, inputs, unknowns, target, value) {
+        Ok(res) => res,
+        Err(SymbolicError::StepLimit { .. }) => None,
+        Err(_) => solve_concrete(data, inputs, unknowns, target, value),
+    }
+}
+
+fn solve_symbolic(
+    data: &[i64],
+    inputs: &[i64],
+    unknowns: &[(Unknown, RangeInclusive<i64>)],
+    target: Target,
+    value: i64,
+) -> Result<Option<Vec<i64>>, SymbolicError> {
+    let mut program = SymbolicProgram::new(data, inputs);
+    let symbols = unknowns
+        .iter()
+        .map(|(u, _)| match *u {
+            Unknown::Memory(addr) => program.symbolize_cell(addr),
+            Unknown::Input(idx) => program.symbolize_input(idx),
+        })
+        .collect_vec();
+    program.run()?;
+
+    let expr = match target {
+        Target::Memory(addr) => program.get(addr),
+        Target::Output(idx) => match program.outputs.get(idx) {
+            Some(e) => e.clone(),
+            None => return Ok(None),
+        },
+    };
+
+    let poly = expr.to_poly();
+    let linear = poly.as_ref().and_then(|p| {
+        symbols
+            .iter()
+            .position(|&s| p.degree_in(s) == 1)
+            .map(|idx| (idx, p.split_linear(symbols[idx])))
+    });
+
+    let mut values: HashMap<Symbol, i64> = HashMap::default();
+    if let Some((solve_idx, (p, q))) = linear {
+        let range = &unknowns[solve_idx].1;
+        let others = (0..unknowns.len())
+            .filter(|&i| i != solve_idx)
+            .collect_vec();
+        for assignment in assignments(&others.iter().map(|&i| &unknowns[i].1).collect_vec()) {
+            for (&i, &v) in others.iter().zip(assignment.iter()) {
+                values.insert(symbols[i], v);
+            }
+            let overflow = SymbolicError::Overflow { pc: program.pc };
+            let p = p.eval(&values).ok_or(overflow)?;
+            let q = q.eval(&values).ok_or(overflow)?;
+            let rest = value.checked_sub(q).ok_or(overflow)?;
+            let solution = if p == 0 {
+                if q == value {
+                    Some(*range.start())
+                } else {
+                    None
+                }
+            } else if rest % p == 0 {
+                Some(rest / p).filter(|x| range.contains(x))
+            } else {
+                None
+            };
+            if let Some(x) = solution {
+                let mut res = vec![0; unknowns.len()];
+                for (&i, &v) in others.iter().zip(assignment.iter()) {
+                    res[i] = v;
+                }
+                res[solve_idx] = x;
+                return Ok(Some(res));
+            }
+        }
+        return Ok(None);
+    }
+
+    for assignment in assignments(&unknowns.iter().map(|(_, r)| r).collect_vec()) {
+        for (&s, &v) in symbols.iter().zip(assignment.iter()) {
+            values.insert(s, v);
+        }
+        let res = match &poly {
+            Some(p) => p.eval(&values),
+            None => program.eval(&expr, &values),
+        };
+        let res = res.ok_or(SymbolicError::Overflow { pc: program.pc })?;
+        if res == value {
+            return Ok(Some(assignment));
+        }
+    }
+    Ok(None)
+}
+
+fn solve_concrete(
+    data: &[i64],
+    inputs: &[i64],
+    unknowns: &[(Unknown, RangeInclusive<i64>)],
+    target: Target,
+    value: i64,
+) -> Option<Vec<i64>> {
+    let image = ProgramImage::new(data);
+    assignments(&unknowns.iter().map(|(_, r)| r).collect_vec()).find(|assignment| {
+        let mut inputs = inputs.to_vec();
+        let mut program = Program::from_image(&image, &[]);
+        for ((u, _), &v) in unknowns.iter().zip(assignment.iter()) {
+            match *u {
+                Unknown::Memory(addr) => program[addr] = v,
+                Unknown::Input(idx) => {
+                    if idx >= inputs.len() {
+                        inputs.resize(idx + 1, 0);
+                    }
+                    inputs[idx] = v;
+                }
+            }
+        }
+        program.inputs = inputs;
+        let mut steps = 0;
+        while program.step() == IntcodeStepResult::Ok && steps < DEFAULT_STEP_LIMIT {
+            steps += 1;
+        }
+        if program.get_status() != IntcodeStepResult::Halt {
+            return false;
+        }
+        match target {
+            Target::Memory(addr) => program[addr] == value,
+            Target::Output(idx) => program.outputs.get(idx) == Some(&value),
+        }
+    })
+}
+
+/// Iterates over every combination of values from `ranges`, in lexicographic order.
+fn assignments<'a>(ranges: &[&'a RangeInclusive<i64>]) -> impl Iterator<Item = Vec<i64>> + 'a {
+    let ranges = ranges.iter().map(|&r| r.clone()).collect_vec();
+    let empty = ranges.is_empty();
+    ranges
+        .into_iter()
+        .multi_cartesian_product()
+        .chain(if empty { Some(vec![]) } else { None })
+}
+
+#[test]
+fn test_symbolic_straight_line() {
+    // mem[0] = (mem[9] + mem[10]) * mem[11]
+    let data = [1, 9, 10, 0, 2, 0, 11, 0, 99, 3, 4, 5];
+    let mut program = SymbolicProgram::new(&data, &[]);
+    let a = program.symbolize_cell(9);
+    let b = program.symbolize_cell(10);
+    program.run().unwrap();
+
+    let poly = program.get(0).to_poly().unwrap();
+    assert_eq!(poly.degree_in(a), 1);
+    assert_eq!(poly.degree_in(b), 1);
+    let values = [(a, 3), (b, 4)].iter().copied().collect();
+    assert_eq!(poly.eval(&values), Some(35));
+}
+
+#[test]
+fn test_symbolic_load() {
+    // The published Day 2 example, with the first instruction's operands made symbolic.
+    let data = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+    for noun in 0..12 {
+        for verb in 0..12 {
+            let mut program = Program::new(&data, &[]);
+            program[1] = noun;
+            program[2] = verb;
+            program.run();
+            let expected = program[0];
+
+            let res = solve_for(
+                &data,
+                &[],
+                &[(Unknown::Memory(1), 0..=11), (Unknown::Memory(2), 0..=11)],
+                Target::Memory(0),
+                expected,
+            )
+            .unwrap();
+            let mut program = Program::new(&data, &[]);
+            program[1] = res[0];
+            program[2] = res[1];
+            program.run();
+            assert_eq!(program[0], expected);
+        }
+    }
+}
+
+#[test]
+fn test_symbolic_branch_fallback() {
+    // Outputs 1 if the input equals 8, otherwise 0 (from Day 5).
+    let data = [3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+    let res = solve_for(
+        &data,
+        &[],
+        &[(Unknown::Input(0), 0..=20)],
+        Target::Output(0),
+        1,
+    );
+    assert_eq!(res, Some(vec![8]));
+
+    let data = [3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1];
+    let mut program = SymbolicProgram::new(&data, &[]);
+    program.symbolize_input(0);
+    assert_eq!(program.run(), Err(SymbolicError::SymbolicBranch { pc: 2 }));
+    let res = solve_for(
+        &data,
+        &[],
+        &[(Unknown::Input(0), 0..=5)],
+        Target::Output(0),
+        0,
+    );
+    assert_eq!(res, Some(vec![0]));
+}
+
+#[test]
+fn test_symbolic_overflow_and_step_limit() {
+    // mem[0] = mem[9] * mem[10], where the product doesn't fit in an `i64`.
+    let data = [2, 9, 10, 0, 99, 0, 0, 0, 0, i64::MAX, 2];
+    let mut program = SymbolicProgram::new(&data, &[]);
+    assert_eq!(program.run(), Err(SymbolicError::Overflow { pc: 0 }));
+    let mut program = SymbolicProgram::new(&data, &[]);
+    let s = program.symbolize_cell(10);
+    program.run().unwrap();
+    let values = [(s, 2)].iter().copied().collect();
+    assert_eq!(program.get(0).to_poly().unwrap().eval(&values), None);
+
+    // Jumps back to the start forever, never reaching the halt.
+    let data = [1105, 1, 0, 99];
+    let mut program = SymbolicProgram::new(&data, &[]).with_step_limit(100);
+    assert_eq!(program.run(), Err(SymbolicError::StepLimit { pc: 0 }));
+    let res = solve_for(
+        &data,
+        &[],
+        &[(Unknown::Memory(3), 99..=99)],
+        Target::Memory(0),
+        1105,
+    );
+    assert_eq!(res, None);
+}